@@ -4,31 +4,24 @@ use image::{GenericImage, ImageBuffer, Pixel, Rgb, RgbImage};
 use itertools::{izip, Itertools};
 
 use rayon::prelude::{IntoParallelRefMutIterator, ParallelBridge, ParallelIterator};
-use serde::Deserialize;
 use std::ops::Deref;
 use std::time::Instant;
 
+/// the calibration which is undistorted unless another one is passed as the first argument, in the OpenCV
+/// `FileStorage` format
+const CALIBRATION: &str = "tests/camera.yaml";
 
+/// the model is whichever one the calibration file specifies
+type Model = CameraModel<AnyProjection, AnyDistortion>;
 
-use std::fs::File;
-use std::io::Read;
-
-#[derive(Debug, Deserialize)]
-struct AppConfig {
-    distortion: PlumbBob,
-}
-
-fn load_config() -> Result<AppConfig, toml::de::Error> {
-    let mut file = File::open("config.toml").unwrap();
-    let mut contents = String::new();
-    file.read_to_string(&mut contents).unwrap();
-
-    toml::from_str(&contents)
+fn load_camera() -> Result<Model, opencv::OpenCvError> {
+    let path = std::env::args().nth(1).unwrap_or_else(|| CALIBRATION.to_string());
+    opencv::FileStorage::open(path)?.any_camera_model()
 }
 
 fn get_distorted_pixel_idx(
     PixelIndex(u, v): PixelIndex<u32>,
-    camera: &CameraModel<Pinhole, PlumbBob>,
+    camera: &Model,
     desired: &Pinhole,
 ) -> PixelIndex<f64> {
     let ray = desired.unproject(&PixelIndex(u as f64, v as f64));
@@ -42,7 +35,7 @@ fn get_distorted_pixel_idx(
 
 fn compute_undistortion_map(
     resolution: (u32, u32),
-    camera: &CameraModel<Pinhole, PlumbBob>,
+    camera: &Model,
     desired: &Pinhole,
 ) -> (Vec<u32>, Vec<u32>, Vec<u32>, Vec<u32>) {
     let (width, height) = resolution;
@@ -69,7 +62,7 @@ fn compute_undistortion_map(
 
 fn undisort_forloop(
     img: &RgbImage,
-    camera: &CameraModel<Pinhole, PlumbBob>,
+    camera: &Model,
     desired: &Pinhole,
 ) -> RgbImage {
     let mut res = RgbImage::new(img.width(), img.height());
@@ -88,12 +81,9 @@ fn undisort_forloop(
         // print!("u, v = {}, {}\n", u, v);
         // the problem is that we need to send in x' and y' from https://docs.opencv.org/4.x/d9/d0c/group__calib3d.html#ga7dfb72c9cf9780a347fbe3d1c47e5d5a
 
-        let PixelIndex(u, v) = get_distorted_pixel_idx(PixelIndex(u, v), camera, desired);
-        // clamp to image size
-        // print!("u, v = {}, {}\n", u, v);
-        let x = u.round() as u32;
-        let y = v.round() as u32;
-        if 0 < x && x < img.width() && 0 < y && y < img.height() {
+        let src_idx = get_distorted_pixel_idx(PixelIndex(u, v), camera, desired);
+        // row and column 0 are valid source pixels as well
+        if let Some(PixelIndex(x, y)) = match_pixel(src_idx, img.dimensions()) {
             *px = *img.get_pixel(x, y);
         }
     }
@@ -118,7 +108,7 @@ fn byte_index<P: Pixel, C: Deref<Target = [P::Subpixel]>>(
 
 fn compute_undistortion_map_linidx(
     img: &RgbImage,
-    camera: &CameraModel<Pinhole, PlumbBob>,
+    camera: &Model,
     desired: &Pinhole,
 ) -> (Vec<usize>, Vec<usize>) {
     let (width, height) = img.dimensions();
@@ -142,7 +132,7 @@ fn compute_undistortion_map_linidx(
 
 fn compute_undistortion_map_byteidx(
     img: &RgbImage,
-    camera: &CameraModel<Pinhole, PlumbBob>,
+    camera: &Model,
     desired: &Pinhole,
 ) -> (Vec<usize>, Vec<usize>) {
     let (width, height) = img.dimensions();
@@ -218,7 +208,7 @@ fn undistort_precomputed_byteidx(
 
 fn compute_undistortion_map_linidx_rayon<'a>(
     res: &'a mut RgbImage,
-    camera: &CameraModel<Pinhole, PlumbBob>,
+    camera: &Model,
     desired: &Pinhole,
 ) -> (Vec<&'a mut Rgb<u8>>, Vec<usize>) {
     let (width, height) = res.dimensions();
@@ -272,7 +262,7 @@ fn match_pixel(idx: PixelIndex<f64>, dimensions: (u32, u32)) -> Option<PixelInde
 
 fn compute_undistortion_map_rows_rayon(
     img: &RgbImage,
-    camera: &CameraModel<Pinhole, PlumbBob>,
+    camera: &Model,
     desired: &Pinhole,
 ) -> Vec<Vec<Option<PixelIndex<u32>>>> {
     let (width, height) = img.dimensions();
@@ -293,12 +283,11 @@ fn compute_undistortion_map_rows_rayon(
 fn undistort_row(
     dst: PixelsMut<Rgb<u8>>,
     src: &RgbImage,
-    row_map: &Vec<Option<PixelIndex<u32>>>,
+    row_map: &[Option<PixelIndex<u32>>],
 ) {
     for (dst, idx) in dst.zip(row_map.iter()) {
-        match idx {
-            Some(PixelIndex(x, y)) => *dst = *src.get_pixel(*x, *y),
-            None => (),
+        if let Some(PixelIndex(x, y)) = idx {
+            *dst = *src.get_pixel(*x, *y)
         }
     }
 }
@@ -316,20 +305,16 @@ fn undistort_precomputed_rows_rayon(
     res
 }
 fn main() {
-    let conf = load_config().unwrap();
+    let camera = load_camera().unwrap();
     let img = image::open("tests/test.jpg").unwrap();
     let img = img.to_rgb8();
 
-    let distortion = conf.distortion;
-    let projection = Pinhole::from_resolution_fov((img.width(), img.height()), (90., 90.));
     let desired = Pinhole::from_resolution_fov((img.width(), img.height()), (90., 90.));
-    let camera = CameraModel::new(projection, distortion);
-    println!("camera_matrix = {:?}", projection.matrix());
-    println!("projection = {:?}", projection);
-    println!("distortion = {:?}", distortion);
+    println!("camera_matrix = {:?}", desired.matrix());
+    println!("projection = {:?}", camera.projection());
+    println!("distortion = {:?}", camera.distortion());
     println!("width = {}", img.width());
     println!("height = {}", img.height());
-    //
 
     let map = compute_undistortion_map((img.width(), img.height()), &camera, &desired);
     let map_linidx = compute_undistortion_map_linidx(&img, &camera, &desired);
@@ -338,6 +323,8 @@ fn main() {
     let mut res_rayon = RgbImage::new(img.width(), img.height());
     let map_rayon = compute_undistortion_map_linidx_rayon(&mut res_rayon, &camera, &desired);
     let map_row_rayon = compute_undistortion_map_rows_rayon(&img, &camera, &desired);
+    let remap = RemapMap::new(&camera, img.dimensions(), &desired, img.dimensions());
+    let remap_nearest = remap.clone().with_interpolation(Interpolation::Nearest);
 
     fn measure<F>(img: &RgbImage, function: F, name: &str)
     where
@@ -356,12 +343,15 @@ fn main() {
     let f2 = |x: &RgbImage| undistort_precomputed(x, &map);
     let f3 = |x: &RgbImage| undistort_precomputed_linidx(x, &map_linidx);
     let f4 = |x: &RgbImage| undistort_precomputed_byteidx(x, &map_byte);
-    // let f5 = |x: &RgbImage|
+    let f5 = |x: &RgbImage| remap_nearest.apply(x);
+    let f6 = |x: &RgbImage| remap.apply(x);
 
     measure(&img, f1, "undistort_forloop");
     measure(&img, f2, "undistort_precomputed");
     measure(&img, f3, "undistort_precomputed_linidx");
     measure(&img, f4, "undistort_precomputed_byteidx");
+    measure(&img, f5, "undistort_remap_map_nearest");
+    measure(&img, f6, "undistort_remap_map_bilinear");
     // measure(&img, f5, "undistort_precomputed_rows_rayon");
 
     // was not able to wrap this into a callable function
@@ -0,0 +1,21 @@
+# reference values of cv2.projectPoints for the 14 coefficient model,
+# the output is pasted into `test_thin_prism_reference` in src/distortion.rs
+import cv2
+import numpy as np
+
+# k1, k2, p1, p2, k3, k4, k5, k6, s1, s2, s3, s4, tauX, tauY
+distortion = np.array(
+    [0.1, 0.01, 0.001, -0.002, 0.0001, 0.05, 0.005, 0.0005,
+     0.002, -0.0005, -0.001, 0.0003, 0.02, -0.01]
+)
+points = np.array([[0.5, -0.25], [-0.3, 0.4], [0.0, 0.0]])
+
+# with an identity camera matrix the pixels are the distorted normalized coordinates
+object_points = np.hstack([points, np.ones((len(points), 1))])
+camera_matrix = np.eye(3)
+projected, _ = cv2.projectPoints(
+    object_points, np.zeros(3), np.zeros(3), camera_matrix, distortion
+)
+
+for (x, y), (xd, yd) in zip(points.tolist(), projected.reshape(-1, 2).tolist()):
+    print(f"(({x!r}, {y!r}), ({xd!r}, {yd!r})),")
@@ -0,0 +1,133 @@
+//! reading of the `calib_<name>.toml` files written by `reference/python/compute_calibration.py`
+use crate::camera::CameraModel;
+use crate::distortion::AnyDistortion;
+use crate::projection::Pinhole;
+
+use serde::Deserialize;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+#[derive(Debug)]
+pub enum CalibrationError {
+    Io(std::io::Error),
+    Parse(toml::de::Error),
+}
+impl fmt::Display for CalibrationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CalibrationError::Io(err) => write!(f, "io error: {}", err),
+            CalibrationError::Parse(err) => write!(f, "parse error: {}", err),
+        }
+    }
+}
+impl std::error::Error for CalibrationError {}
+impl From<std::io::Error> for CalibrationError {
+    fn from(err: std::io::Error) -> Self {
+        CalibrationError::Io(err)
+    }
+}
+impl From<toml::de::Error> for CalibrationError {
+    fn from(err: toml::de::Error) -> Self {
+        CalibrationError::Parse(err)
+    }
+}
+
+/// a calibrated camera together with the optimal new projection of `cv::getOptimalNewCameraMatrix`,
+/// which is the target for undistortion
+#[derive(Debug, Clone, Deserialize)]
+pub struct Calibration {
+    pub projection: Pinhole,
+    pub distortion: AnyDistortion,
+    pub new_projection: Pinhole,
+    /// the size of the calibration images, older files do not store it
+    #[serde(default)]
+    pub image_width: Option<u32>,
+    #[serde(default)]
+    pub image_height: Option<u32>,
+}
+
+impl Calibration {
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, CalibrationError> {
+        Self::parse(&fs::read_to_string(path)?)
+    }
+    pub fn parse(contents: &str) -> Result<Self, CalibrationError> {
+        Ok(toml::from_str(contents)?)
+    }
+    pub fn resolution(&self) -> Option<(u32, u32)> {
+        Some((self.image_width?, self.image_height?))
+    }
+    pub fn camera_model(&self) -> CameraModel<Pinhole, AnyDistortion> {
+        let model = CameraModel::new(self.projection, self.distortion);
+        match self.resolution() {
+            Some(resolution) => model.with_resolution(resolution),
+            None => model,
+        }
+    }
+}
+
+/// reads the camera model and the new projection from a calibration of the python script
+pub fn read_calibration<P: AsRef<Path>>(
+    path: P,
+) -> Result<(CameraModel<Pinhole, AnyDistortion>, Pinhole), CalibrationError> {
+    let calibration = Calibration::open(path)?;
+    Ok((calibration.camera_model(), calibration.new_projection))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CameraProjection, CameraRay, ImageTransform, PixelIndex, PlumbBob, RemapMap};
+
+    #[test]
+    fn test_read_calibration() {
+        let (camera, new_projection) = read_calibration("tests/calib_left.toml").unwrap();
+        let projection = camera.projection();
+        assert_eq!(projection.fx, 531.5211371245931);
+        assert_eq!(projection.skew, 0.0);
+        assert_eq!(new_projection.cy, 243.6041730656568);
+        assert!(matches!(
+            camera.distortion(),
+            AnyDistortion::PlumbBob(PlumbBob { k1, .. }) if *k1 == -0.28340811217029
+        ));
+        // the principal point of the new projection looks along the optical axis
+        let center = camera
+            .project(new_projection.unproject(&PixelIndex(new_projection.cx, new_projection.cy)));
+        assert!((center.0 - projection.cx).abs() < 1e-9);
+        assert!((center.1 - projection.cy).abs() < 1e-9);
+        let map = RemapMap::new(&camera, (640, 480), &new_projection, (640, 480));
+        assert_eq!(map.resolution(), (640, 480));
+        // the script does not store the image size
+        assert_eq!(camera.resolution(), None);
+    }
+    #[test]
+    fn test_resolution() {
+        let contents = include_str!("../tests/calib_left.toml");
+        let contents = format!("image_width = 640\nimage_height = 480\n{}", contents);
+        let camera = Calibration::parse(&contents).unwrap().camera_model();
+        assert_eq!(camera.resolution(), Some((640, 480)));
+    }
+    #[test]
+    fn test_transform_image() {
+        let contents = include_str!("../tests/calib_left.toml");
+        let contents = format!("image_width = 640\nimage_height = 480\n{}", contents);
+        let camera = Calibration::parse(&contents).unwrap().camera_model();
+        let transform = ImageTransform::Rotate90Clockwise;
+        let rotated = camera.transform_image(transform).unwrap();
+        assert_eq!(rotated.resolution(), Some((480, 640)));
+        let ray = CameraRay::new(0.2, -0.1, 1.0);
+        let expected = transform.pixel(&camera.project(ray), (640, 480));
+        let pixel = rotated.project(transform.ray(&ray));
+        assert!((pixel.0 - expected.0).abs() < 1e-9);
+        assert!((pixel.1 - expected.1).abs() < 1e-9);
+    }
+    #[test]
+    fn test_missing_distortion() {
+        let contents = include_str!("../tests/calib_left.toml");
+        let (head, _) = contents.split_once("[distortion]").unwrap();
+        assert!(matches!(
+            Calibration::parse(head),
+            Err(CalibrationError::Parse(_))
+        ));
+    }
+}
@@ -1,9 +1,10 @@
-use crate::distortion::CameraDistortion;
-use crate::projection::CameraProjection;
-
-use nalgebra::{
-    Isometry3, Vector3,
+use crate::distortion::{
+    CameraDistortion, DistortionError, IterativeSolver, TransformableDistortion,
 };
+use crate::projection::{AffineIntrinsics, CameraProjection, ImageTransform};
+
+use nalgebra::{Isometry3, Point3, RealField, Unit, Vector3};
+use serde::Deserialize;
 use std::fmt::Debug;
 
 #[derive(Clone, PartialEq, Debug)]
@@ -23,70 +24,231 @@ use std::fmt::Debug;
 
 pub struct PixelIndex<T>(pub T, pub T);
 
-impl PixelIndex<f64> {
-    pub fn x(&self) -> &f64 {
+impl<T> PixelIndex<T> {
+    pub fn x(&self) -> &T {
         &self.0
     }
 
-    pub fn y(&self) -> &f64 {
+    pub fn y(&self) -> &T {
         &self.1
     }
+}
 
+impl PixelIndex<f64> {
     pub fn nearest(self) -> PixelIndex<u32> {
         PixelIndex(self.x().round() as u32, self.y().round() as u32)
     }
 }
 
 #[derive(Debug, Clone, Copy)]
-pub struct CameraRay {
-    pub vector: Vector3<f64>,
+pub struct CameraRay<T = f64> {
+    pub vector: Vector3<T>,
 }
-impl PartialEq for CameraRay {
+impl<T: RealField + Copy> PartialEq for CameraRay<T> {
     fn eq(&self, other: &Self) -> bool {
-        let angle = self.vector.angle(&other.vector);
-        println!("angle: {}, eps: {}", angle, f64::EPSILON.sqrt());
-        angle < f64::EPSILON.sqrt()
+        // atan2 of |a x b| and a . b is accurate for small angles, unlike acos
+        let angle = self
+            .vector
+            .cross(&other.vector)
+            .norm()
+            .atan2(self.vector.dot(&other.vector));
+        angle < T::default_epsilon().sqrt()
     }
 }
-impl Eq for CameraRay {}
+impl<T: RealField + Copy> Eq for CameraRay<T> {}
 
-impl CameraRay {
-    pub fn new(x: f64, y: f64, z: f64) -> Self {
+impl<T: RealField + Copy> CameraRay<T> {
+    pub fn new(x: T, y: T, z: T) -> Self {
         Self {
             vector: Vector3::new(x, y, z),
         }
     }
-    pub fn xy(&self) -> (f64, f64) {
+    pub fn xy(&self) -> (T, T) {
         (self.vector.x, self.vector.y)
     }
+    /// the point where the ray meets the image plane `z = 1`, the input of the planar distortions
+    pub fn plane_xy(&self) -> (T, T) {
+        (self.vector.x / self.vector.z, self.vector.y / self.vector.z)
+    }
+}
+
+/// a ray in world coordinates
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Ray {
+    pub origin: Point3<f64>,
+    pub direction: Unit<Vector3<f64>>,
+}
+impl Ray {
+    pub fn at(&self, distance: f64) -> Point3<f64> {
+        self.origin + self.direction.into_inner() * distance
+    }
 }
 
+#[derive(Debug, Clone, Deserialize)]
 pub struct CameraModel<T, V> {
     projection: T,
     distortion: V,
+    /// the size of the images as `(width, height)`, if known
+    #[serde(default)]
+    resolution: Option<(u32, u32)>,
 }
-impl<T, V> CameraModel<T, V>
-where
-    T: CameraProjection,
-    V: CameraDistortion,
-{
+impl<T, V> CameraModel<T, V> {
     pub fn new(projection: T, distortion: V) -> Self {
         Self {
             projection,
             distortion,
+            resolution: None,
         }
     }
+    pub fn with_resolution(mut self, resolution: (u32, u32)) -> Self {
+        self.resolution = Some(resolution);
+        self
+    }
     pub fn distortion(&self) -> &V {
         &self.distortion
     }
     pub fn projection(&self) -> &T {
         &self.projection
     }
-    pub fn project<U: Into<CameraRay>>(&self, ray: U) -> PixelIndex<f64> {
+    pub fn resolution(&self) -> Option<(u32, u32)> {
+        self.resolution
+    }
+    /// the top left and bottom right corner of the image, the pixel centers are at integer coordinates, so
+    /// the image covers `-0.5` to `width - 0.5`
+    pub fn image_bounds(&self) -> Option<(PixelIndex<f64>, PixelIndex<f64>)> {
+        let (width, height) = self.resolution?;
+        Some((
+            PixelIndex(-0.5, -0.5),
+            PixelIndex(width as f64 - 0.5, height as f64 - 0.5),
+        ))
+    }
+    /// whether the pixel lies inside of the image, without a resolution the image is unbounded
+    pub fn is_in_image(&self, PixelIndex(u, v): &PixelIndex<f64>) -> bool {
+        match self.image_bounds() {
+            Some((min, max)) => (min.0..max.0).contains(u) && (min.1..max.1).contains(v),
+            None => true,
+        }
+    }
+    /// the scalar type `S` of the ray is the one the projection and the distortion are implemented for
+    pub fn project<S, U>(&self, ray: U) -> PixelIndex<S>
+    where
+        S: RealField + Copy,
+        T: CameraProjection<S>,
+        V: CameraDistortion<S>,
+        U: Into<CameraRay<S>>,
+    {
         let ray = ray.into();
         let distorted = self.distortion().distort(&ray);
         self.projection().project(&distorted)
     }
+    /// projects the ray, `None` if it is outside of the domain of the distortion or of the projection
+    pub fn try_project<S, U>(&self, ray: U) -> Option<PixelIndex<S>>
+    where
+        S: RealField + Copy,
+        T: CameraProjection<S>,
+        V: CameraDistortion<S>,
+        U: Into<CameraRay<S>>,
+    {
+        let ray = ray.into();
+        if !self.distortion().is_valid_ray(&ray) {
+            return None;
+        }
+        let distorted = self.distortion().distort(&ray);
+        self.projection().try_project(&distorted)
+    }
+    /// returns the ideal ray which is imaged at the given pixel, this inverts the projection and then the distortion
+    pub fn unproject<S>(&self, pixel: &PixelIndex<S>) -> Result<CameraRay<S>, DistortionError>
+    where
+        S: RealField + Copy,
+        T: CameraProjection<S>,
+        V: CameraDistortion<S>,
+    {
+        self.unproject_with(pixel, &IterativeSolver::default())
+    }
+    pub fn unproject_with<S>(
+        &self,
+        pixel: &PixelIndex<S>,
+        solver: &IterativeSolver,
+    ) -> Result<CameraRay<S>, DistortionError>
+    where
+        S: RealField + Copy,
+        T: CameraProjection<S>,
+        V: CameraDistortion<S>,
+    {
+        let distorted = self.projection().unproject(pixel);
+        self.distortion().undistort_with(&distorted, solver)
+    }
+}
+
+/// resampling the images of the camera, the resolution follows the image
+impl<T: AffineIntrinsics, V: Clone> CameraModel<T, V> {
+    fn map_pixels(&self, projection: T, resolution: impl Fn(u32, u32) -> (u32, u32)) -> Self {
+        Self {
+            projection,
+            distortion: self.distortion.clone(),
+            resolution: self
+                .resolution
+                .map(|(width, height)| resolution(width, height)),
+        }
+    }
+    /// see `AffineIntrinsics::scale`, the resolution is rounded
+    pub fn scale(&self, sx: f64, sy: f64) -> Self {
+        self.map_pixels(self.projection.scale(sx, sy), |width, height| {
+            (
+                (width as f64 * sx).round() as u32,
+                (height as f64 * sy).round() as u32,
+            )
+        })
+    }
+    /// see `AffineIntrinsics::crop`, the resolution becomes the size of the region of interest
+    ///
+    /// # Panics
+    /// if the region of interest is empty or does not fit into the resolution
+    pub fn crop(&self, roi: (u32, u32, u32, u32)) -> Self {
+        let (x, y, width, height) = roi;
+        assert!(width > 0 && height > 0, "empty region of interest");
+        if let Some((image_width, image_height)) = self.resolution {
+            let fits =
+                |start: u32, size: u32, end: u32| start.checked_add(size).is_some_and(|e| e <= end);
+            assert!(
+                fits(x, width, image_width) && fits(y, height, image_height),
+                "the region of interest {:?} exceeds the image of {} x {}",
+                roi,
+                image_width,
+                image_height
+            );
+        }
+        self.map_pixels(self.projection.crop(roi), |_, _| (roi.2, roi.3))
+    }
+    /// see `AffineIntrinsics::bin`, incomplete blocks at the border are dropped
+    ///
+    /// # Panics
+    /// if `factor` is zero
+    pub fn bin(&self, factor: u32) -> Self {
+        self.map_pixels(self.projection.bin(factor), |width, height| {
+            (width / factor, height / factor)
+        })
+    }
+    /// see `AffineIntrinsics::pyramid_level`, every level halves the size rounding up like `cv::pyrDown`
+    pub fn pyramid_level(&self, n: u32) -> Self {
+        self.map_pixels(self.projection.pyramid_level(n), |width, height| {
+            (0..n).fold((width, height), |(w, h), _| (w.div_ceil(2), h.div_ceil(2)))
+        })
+    }
+}
+
+impl<T: AffineIntrinsics, V: TransformableDistortion> CameraModel<T, V> {
+    /// the model of the rotated or flipped image, the ray `r` of this model is the ray `transform.ray(r)` of
+    /// the new one. `None` without a resolution, for quarter turns of skewed intrinsics or if the distortion
+    /// can not be transformed, see `TransformableDistortion`
+    pub fn transform_image(&self, transform: ImageTransform) -> Option<Self> {
+        let resolution = self.resolution?;
+        Some(Self {
+            projection: self.projection.transform_image(transform, resolution)?,
+            distortion: self.distortion.transform_image(transform)?,
+            resolution: Some(transform.resolution(resolution)),
+        })
+    }
 }
 
 pub struct Camera<T, V>
@@ -95,6 +257,7 @@ where
     V: CameraDistortion,
 {
     model: CameraModel<T, V>,
+    /// the pose of the camera in the world, i.e. it transforms points from the camera frame into the world frame
     view: Isometry3<f64>,
 }
 impl<T, V> Camera<T, V>
@@ -102,6 +265,7 @@ where
     T: CameraProjection,
     V: CameraDistortion,
 {
+    /// `view` is the camera-to-world transform, its translation is the camera center in world coordinates
     pub fn new(model: CameraModel<T, V>, view: Isometry3<f64>) -> Self {
         Self { model, view }
     }
@@ -111,12 +275,424 @@ where
     pub fn model(&self) -> &CameraModel<T, V> {
         &self.model
     }
+    /// projects a point given in world coordinates into the image, points outside of the domain of the model,
+    /// e.g. behind a pinhole camera, return `None`
+    pub fn project_point(&self, point: &Point3<f64>) -> Option<PixelIndex<f64>> {
+        let local = self.view.inverse_transform_point(point);
+        // the distortions of the normalized coordinates expect rays on the image plane
+        let ray = if local.z > 0.0 {
+            CameraRay::new(local.x / local.z, local.y / local.z, 1.0)
+        } else {
+            CameraRay {
+                vector: local.coords,
+            }
+        };
+        self.model.try_project(ray)
+    }
+    /// returns the world space ray starting at the camera center which is imaged at the given pixel
+    pub fn unproject_to_world(&self, pixel: &PixelIndex<f64>) -> Result<Ray, DistortionError> {
+        let ray = self.model.unproject(pixel)?;
+        Ok(Ray {
+            origin: Point3::from(self.view.translation.vector),
+            direction: Unit::new_normalize(self.view.transform_vector(&ray.vector)),
+        })
+    }
 }
 
-pub struct Fisheye {
-    pub fx: f64,
-    pub fy: f64,
-    pub cx: f64,
-    pub cy: f64,
-    pub skew: f64,
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AnyDistortion, AnyProjection, Ideal, Pinhole, PlumbBob};
+    use approx::assert_abs_diff_eq;
+    use nalgebra::{Translation3, UnitQuaternion};
+
+    fn camera() -> Camera<Pinhole, PlumbBob> {
+        let projection = Pinhole::new(800.0, 780.0, 320.0, 240.0, 0.0);
+        let distortion = PlumbBob::new(0.1, -0.05, 0.001, 0.002, 0.0);
+        // camera at x = 2 in the world, rotated such that it looks along the world -x axis
+        let view = Isometry3::from_parts(
+            Translation3::new(2.0, 0.0, 0.0),
+            UnitQuaternion::from_axis_angle(&Vector3::y_axis(), -std::f64::consts::FRAC_PI_2),
+        );
+        Camera::new(CameraModel::new(projection, distortion), view)
+    }
+
+    #[test]
+    fn test_runtime_selected_model() {
+        let config = r#"
+            [projection]
+            type = "Pinhole"
+            parameters = { fx = 800.0, fy = 780.0, cx = 320.0, cy = 240.0, skew = 0.0 }
+            [distortion]
+            type = "PlumbBob"
+            coefficients = { k1 = 0.1, k2 = -0.05, p1 = 0.001, p2 = 0.002, k3 = 0.0 }
+        "#;
+        let any: CameraModel<AnyProjection, AnyDistortion> = toml::from_str(config).unwrap();
+        let model = camera().model().clone();
+        let ray = CameraRay::new(0.2, -0.1, 1.0);
+        let (dst, src) = (any.project(ray), model.project(ray));
+        assert_eq!((dst.0, dst.1), (src.0, src.1));
+    }
+    #[test]
+    fn test_unproject_roundtrip() {
+        let projection = Pinhole::new(800.0, 780.0, 320.0, 240.0, 0.5);
+        let distortion = PlumbBob::new(0.3, -0.1, 0.01, -0.02, 0.05);
+        let camera = CameraModel::new(projection, distortion);
+        for (u, v) in [(320.0, 240.0), (0.0, 0.0), (639.0, 12.0), (100.0, 470.0)] {
+            let src = PixelIndex(u, v);
+            let ray = camera.unproject(&src).unwrap();
+            let dst = camera.project(ray);
+            assert_abs_diff_eq!(src.0, dst.0, epsilon = 1e-6);
+            assert_abs_diff_eq!(src.1, dst.1, epsilon = 1e-6);
+        }
+    }
+    #[test]
+    fn test_single_precision_model() {
+        let projection = Pinhole::new(800.0f32, 780.0, 320.0, 240.0, 0.5);
+        let distortion = PlumbBob::new(0.3f32, -0.1, 0.01, -0.02, 0.05);
+        let single = CameraModel::new(projection, distortion);
+        let model = CameraModel::new(
+            Pinhole::new(800.0, 780.0, 320.0, 240.0, 0.5),
+            PlumbBob::new(0.3, -0.1, 0.01, -0.02, 0.05),
+        );
+        for (u, v) in [
+            (320.0f32, 240.0f32),
+            (0.0, 0.0),
+            (639.0, 12.0),
+            (100.0, 470.0),
+        ] {
+            let ray = single.unproject(&PixelIndex(u, v)).unwrap();
+            let dst = single.project(ray);
+            assert_abs_diff_eq!(u, dst.0, epsilon = 1e-2);
+            assert_abs_diff_eq!(v, dst.1, epsilon = 1e-2);
+
+            let reference = model.unproject(&PixelIndex(u as f64, v as f64)).unwrap();
+            let (x, y) = ray.xy();
+            assert_abs_diff_eq!(x as f64, reference.vector.x, epsilon = 1e-5);
+            assert_abs_diff_eq!(y as f64, reference.vector.y, epsilon = 1e-5);
+        }
+    }
+    #[test]
+    fn test_dual_numbers() {
+        use num_dual::Dual64;
+        // derivatives with respect to k1, compared with central differences
+        let d = Dual64::from;
+        let model = |k1: Dual64| {
+            CameraModel::new(
+                Pinhole::new(d(800.0), d(780.0), d(320.0), d(240.0), d(0.5)),
+                PlumbBob::new(k1, d(-0.1), d(0.01), d(-0.02), d(0.05)),
+            )
+        };
+        let k1 = d(0.3).derivative();
+        let h = 1e-6;
+        let difference = |f: &dyn Fn(Dual64) -> (Dual64, Dual64)| {
+            let (u1, v1) = f(d(0.3 + h));
+            let (u0, v0) = f(d(0.3 - h));
+            ((u1.re - u0.re) / (2.0 * h), (v1.re - v0.re) / (2.0 * h))
+        };
+
+        let ray = CameraRay::new(d(0.3), d(-0.2), d(1.0));
+        let project = |k1| {
+            let PixelIndex(u, v) = model(k1).project(ray);
+            (u, v)
+        };
+        let (u, v) = project(k1);
+        let (du, dv) = difference(&project);
+        assert_abs_diff_eq!(u.eps, du, epsilon = 1e-4);
+        assert_abs_diff_eq!(v.eps, dv, epsilon = 1e-4);
+        assert!(u.eps.abs() > 1.0);
+
+        let pixel = PixelIndex(d(100.0), d(400.0));
+        let undistort = |k1| model(k1).unproject(&pixel).unwrap().xy();
+        let (x, y) = undistort(k1);
+        let (dx, dy) = difference(&undistort);
+        assert_abs_diff_eq!(x.eps, dx, epsilon = 1e-6);
+        assert_abs_diff_eq!(y.eps, dy, epsilon = 1e-6);
+        assert!(x.eps.abs() > 1e-3);
+    }
+    #[test]
+    fn test_try_project() {
+        let model = camera().model().clone();
+        assert!(model.try_project(CameraRay::new(0.2, -0.1, 1.0)).is_some());
+        // the plumb bob distortion is defined on the image plane in front of the camera
+        assert!(model.try_project(CameraRay::new(0.2, -0.1, -1.0)).is_none());
+
+        let fisheye = CameraModel::new(
+            Pinhole::new(300.0, 300.0, 320.0, 240.0, 0.0),
+            crate::Fisheye {
+                k1: 0.02,
+                k2: 0.0,
+                k3: 0.0,
+                k4: 0.0,
+                s: 0.0,
+            },
+        );
+        let camera = Camera::new(fisheye, Isometry3::identity());
+        // a fisheye sees points slightly behind the camera
+        let behind = camera.project_point(&Point3::new(1.0, 0.0, -0.1)).unwrap();
+        assert!(behind.0 > 320.0 + 300.0 * std::f64::consts::FRAC_PI_2);
+        assert!(camera.project_point(&Point3::new(0.0, 0.0, -1.0)).is_none());
+
+        // the division model folds back at r_u = 1 / (2 sqrt(lambda1)) ~ 0.707
+        let division = CameraModel::new(
+            Pinhole::new(300.0, 300.0, 320.0, 240.0, 0.0),
+            crate::Division::one_parameter(0.5),
+        );
+        assert!(division
+            .try_project(CameraRay::new(0.7, 0.0, 1.0))
+            .is_some());
+        assert!(division
+            .try_project(CameraRay::new(1.0, 0.0, 1.0))
+            .is_none());
+        let division = CameraModel::new(
+            Pinhole::new(300.0, 300.0, 320.0, 240.0, 0.0),
+            crate::Division::new(0.5, 0.1),
+        );
+        assert!(division
+            .try_project(CameraRay::new(1.0, 0.0, 1.0))
+            .is_none());
+    }
+    #[test]
+    fn test_planar_distortions_scale_invariant() {
+        use crate::{Division, FovDistortion, PinholeDistortion, Rational, ThinPrism};
+        let plumb_bob = PlumbBob::new(0.1, -0.05, 0.001, 0.002, 0.01);
+        let mut rational = Rational::from(plumb_bob);
+        rational.k4 = 0.2;
+        let mut thin_prism = ThinPrism::from(rational);
+        (thin_prism.s1, thin_prism.tau_x) = (0.01, 0.02);
+        for distortion in [
+            PinholeDistortion::PlumbBob(plumb_bob),
+            PinholeDistortion::Rational(rational),
+            PinholeDistortion::ThinPrism(thin_prism),
+            PinholeDistortion::Fov(FovDistortion::new(0.9)),
+            PinholeDistortion::Division(Division::new(-0.2, 0.05)),
+        ] {
+            let model = CameraModel::new(Pinhole::new(800.0, 780.0, 320.0, 240.0, 0.0), distortion);
+            // the same direction at a different distance is imaged at the same pixel
+            let near = model.project(CameraRay::new(0.3, -0.2, 1.0));
+            let far = model.project(CameraRay::new(0.6, -0.4, 2.0));
+            assert_abs_diff_eq!(near.0, far.0, epsilon = 1e-9);
+            assert_abs_diff_eq!(near.1, far.1, epsilon = 1e-9);
+            let ray = model.unproject(&near).unwrap();
+            assert_eq!(ray, CameraRay::new(0.3, -0.2, 1.0), "{:?}", distortion);
+        }
+    }
+    #[test]
+    fn test_resolution() {
+        let model = camera().model().clone();
+        assert_eq!(model.resolution(), None);
+        assert!(model.image_bounds().is_none());
+        assert!(model.is_in_image(&PixelIndex(-100.0, 1e6)));
+
+        let model = model.with_resolution((640, 480));
+        assert_eq!(model.resolution(), Some((640, 480)));
+        let (min, max) = model.image_bounds().unwrap();
+        assert_eq!((min.0, min.1, max.0, max.1), (-0.5, -0.5, 639.5, 479.5));
+        assert!(model.is_in_image(&PixelIndex(-0.5, 0.0)));
+        assert!(model.is_in_image(&PixelIndex(639.4, 479.4)));
+        assert!(!model.is_in_image(&PixelIndex(639.5, 240.0)));
+        assert!(!model.is_in_image(&PixelIndex(320.0, -0.6)));
+
+        let config = r#"
+            resolution = [640, 480]
+            projection = { fx = 800.0, fy = 780.0, cx = 320.0, cy = 240.0, skew = 0.0 }
+            distortion = { k1 = 0.1, k2 = -0.05, p1 = 0.001, p2 = 0.002, k3 = 0.0 }
+        "#;
+        let model: CameraModel<Pinhole, PlumbBob> = toml::from_str(config).unwrap();
+        assert_eq!(model.resolution(), Some((640, 480)));
+    }
+    #[test]
+    fn test_resample() {
+        let model = camera().model().clone().with_resolution((640, 480));
+        let ray = CameraRay::new(0.2, -0.1, 1.0);
+        let PixelIndex(u, v) = model.project(ray);
+
+        let scaled = model.scale(0.5, 0.5);
+        assert_eq!(scaled.resolution(), Some((320, 240)));
+        assert_eq!(scaled.distortion(), model.distortion());
+        let pixel = scaled.project(ray);
+        assert_abs_diff_eq!(pixel.0, 0.5 * u - 0.25, epsilon = 1e-9);
+        assert_abs_diff_eq!(pixel.1, 0.5 * v - 0.25, epsilon = 1e-9);
+
+        let cropped = model.crop((100, 50, 200, 100));
+        assert_eq!(cropped.resolution(), Some((200, 100)));
+        let pixel = cropped.project(ray);
+        assert_abs_diff_eq!(pixel.0, u - 100.0, epsilon = 1e-9);
+        assert_abs_diff_eq!(pixel.1, v - 50.0, epsilon = 1e-9);
+
+        let odd = model.clone().with_resolution((641, 481));
+        let binned = odd.bin(2);
+        assert_eq!(binned.resolution(), Some((320, 240)));
+        assert_eq!(binned.projection(), scaled.projection());
+
+        let level = odd.pyramid_level(3);
+        assert_eq!(level.resolution(), Some((81, 61)));
+        let pixel = level.project(ray);
+        assert_abs_diff_eq!(pixel.0, u / 8.0, epsilon = 1e-9);
+        assert_abs_diff_eq!(pixel.1, v / 8.0, epsilon = 1e-9);
+        // unprojecting the resampled pixel gives back the ray through the distortion
+        assert_eq!(level.unproject(&pixel).unwrap(), ray);
+    }
+    #[test]
+    #[should_panic(expected = "binning factor")]
+    fn test_bin_by_zero() {
+        camera().model().clone().with_resolution((640, 480)).bin(0);
+    }
+    #[test]
+    #[should_panic(expected = "exceeds the image")]
+    fn test_crop_outside_image() {
+        let model = camera().model().clone().with_resolution((640, 480));
+        model.crop((600, 0, 100, 100));
+    }
+    const TRANSFORMS: [ImageTransform; 5] = [
+        ImageTransform::Rotate90Clockwise,
+        ImageTransform::Rotate180,
+        ImageTransform::Rotate90Counterclockwise,
+        ImageTransform::FlipHorizontal,
+        ImageTransform::FlipVertical,
+    ];
+
+    fn assert_transform_image<T, V>(model: &CameraModel<T, V>, transforms: &[ImageTransform])
+    where
+        T: AffineIntrinsics + CameraProjection + PartialEq + Debug,
+        V: TransformableDistortion + CameraDistortion + PartialEq + Debug,
+    {
+        let resolution = model.resolution().unwrap();
+        for &transform in transforms {
+            let transformed = model.transform_image(transform).unwrap();
+            assert_eq!(
+                transformed.resolution(),
+                Some(transform.resolution(resolution))
+            );
+            for (x, y) in [(0.0, 0.0), (0.2, -0.1), (-0.3, 0.25), (0.4, 0.3)] {
+                let ray = CameraRay::new(x, y, 1.0);
+                let expected = transform.pixel(&model.project(ray), resolution);
+                let pixel = transformed.project(transform.ray(&ray));
+                assert_abs_diff_eq!(pixel.0, expected.0, epsilon = 1e-9);
+                assert_abs_diff_eq!(pixel.1, expected.1, epsilon = 1e-9);
+            }
+            let back = transformed.transform_image(transform.inverse()).unwrap();
+            assert_eq!(back.projection(), model.projection(), "{:?}", transform);
+            assert_eq!(back.distortion(), model.distortion(), "{:?}", transform);
+            assert_eq!(back.resolution(), Some(resolution));
+        }
+    }
+    #[test]
+    fn test_transform_image() {
+        let model = camera().model().clone().with_resolution((640, 480));
+        assert_transform_image(&model, &TRANSFORMS);
+        let fisheye = crate::Fisheye {
+            k1: 0.02,
+            k2: -0.003,
+            k3: 0.0005,
+            k4: -0.0001,
+            s: 0.0,
+        };
+        let pinhole = Pinhole::new(300.0, 310.0, 330.0, 235.0, 0.0);
+        assert_transform_image(
+            &CameraModel::new(pinhole, fisheye).with_resolution((640, 480)),
+            &TRANSFORMS,
+        );
+        let projection = crate::projection::Fisheye {
+            fx: 300.0,
+            fy: 310.0,
+            cx: 330.0,
+            cy: 235.0,
+            skew: 0.0,
+        };
+        assert_transform_image(
+            &CameraModel::new(projection, Ideal {}).with_resolution((640, 480)),
+            &TRANSFORMS,
+        );
+
+        // a sensor tilted about one axis can be turned by 90°, one tilted about both only by 180°
+        let prism = crate::ThinPrism {
+            k1: 0.1,
+            k2: 0.01,
+            p1: 0.001,
+            p2: -0.002,
+            k3: 0.0001,
+            k4: 0.05,
+            k5: 0.005,
+            k6: 0.0005,
+            s1: 0.002,
+            s2: -0.0005,
+            s3: -0.001,
+            s4: 0.0003,
+            tau_x: 0.02,
+            tau_y: 0.0,
+        };
+        let tilted = CameraModel::new(pinhole, prism).with_resolution((640, 480));
+        assert_transform_image(&tilted, &TRANSFORMS);
+        let tilted = CameraModel::new(
+            pinhole,
+            crate::ThinPrism {
+                tau_y: -0.01,
+                ..prism
+            },
+        )
+        .with_resolution((640, 480));
+        assert_transform_image(
+            &tilted,
+            &[
+                ImageTransform::Rotate180,
+                ImageTransform::FlipHorizontal,
+                ImageTransform::FlipVertical,
+            ],
+        );
+        assert!(tilted
+            .transform_image(ImageTransform::Rotate90Counterclockwise)
+            .is_none());
+
+        // a quarter turn swaps the focal lengths and the tangential coefficients
+        let rotated = model
+            .transform_image(ImageTransform::Rotate90Clockwise)
+            .unwrap();
+        assert_eq!(rotated.projection().fx, 780.0);
+        assert_eq!(rotated.projection().cx, 479.0 - 240.0);
+        assert_eq!(rotated.projection().cy, 320.0);
+        assert_eq!(
+            (rotated.distortion().p1, rotated.distortion().p2),
+            (0.002, -0.001)
+        );
+
+        // the skew can not be turned by 90°, and the resolution is needed for the principal point
+        let skewed = CameraModel::new(Pinhole::new(800.0, 780.0, 320.0, 240.0, 0.5), Ideal {});
+        assert!(skewed.transform_image(ImageTransform::Rotate180).is_none());
+        let skewed = skewed.with_resolution((640, 480));
+        assert!(skewed
+            .transform_image(ImageTransform::Rotate90Clockwise)
+            .is_none());
+        let flipped = skewed
+            .transform_image(ImageTransform::FlipHorizontal)
+            .unwrap();
+        assert_eq!(flipped.projection().skew, -0.5);
+    }
+    #[test]
+    fn test_view_is_camera_to_world() {
+        let camera = camera();
+        // the optical axis points along world -x, so the world origin is imaged at the principal point
+        let center = camera.project_point(&Point3::new(0.0, 0.0, 0.0)).unwrap();
+        assert_abs_diff_eq!(center.0, 320.0, epsilon = 1e-9);
+        assert_abs_diff_eq!(center.1, 240.0, epsilon = 1e-9);
+        // the camera x axis is the world z axis
+        let right = camera.project_point(&Point3::new(0.0, 0.0, 0.2)).unwrap();
+        assert!(right.0 > 320.0);
+    }
+    #[test]
+    fn test_project_point_behind_camera() {
+        let camera = camera();
+        assert_eq!(camera.project_point(&Point3::new(3.0, 0.0, 0.0)), None);
+        assert_eq!(camera.project_point(&Point3::new(2.0, 1.0, 0.0)), None);
+    }
+    #[test]
+    fn test_unproject_to_world_roundtrip() {
+        let camera = camera();
+        let point = Point3::new(-1.0, 0.3, -0.4);
+        let pixel = camera.project_point(&point).unwrap();
+        let ray = camera.unproject_to_world(&pixel).unwrap();
+        assert_abs_diff_eq!(ray.origin, Point3::new(2.0, 0.0, 0.0), epsilon = 1e-12);
+        let distance = (point - ray.origin).norm();
+        assert_abs_diff_eq!(ray.at(distance), point, epsilon = 1e-6);
+    }
 }
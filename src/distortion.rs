@@ -1,42 +1,272 @@
 use crate::camera::CameraRay;
+use crate::projection::ImageTransform;
 
-
+use nalgebra::{DMatrix, DVector, Matrix2, Matrix3, RealField, Vector2, Vector3};
 use serde::Deserialize;
+use std::fmt;
 
-pub trait CameraDistortion {
+/// the distortion of rays in front of the projection, `T` is the scalar type
+pub trait CameraDistortion<T: RealField + Copy = f64> {
     /// given a ray in camera, return a distorted ray
-    fn distort(&self, ray: &CameraRay) -> CameraRay;
+    fn distort(&self, ray: &CameraRay<T>) -> CameraRay<T>;
+    /// given a distorted ray, return the ideal ray, using the given solver settings
+    fn undistort_with(
+        &self,
+        ray: &CameraRay<T>,
+        solver: &IterativeSolver,
+    ) -> Result<CameraRay<T>, DistortionError>;
+    /// given a distorted ray, return the ideal ray, using the default solver settings
+    fn undistort(&self, ray: &CameraRay<T>) -> Result<CameraRay<T>, DistortionError> {
+        self.undistort_with(ray, &IterativeSolver::default())
+    }
+    /// whether the ray is in the domain of the distortion, by default the rays on the image plane in front
+    /// of the camera, where the distortions of the normalized coordinates are defined
+    fn is_valid_ray(&self, ray: &CameraRay<T>) -> bool {
+        ray.vector.z > T::zero()
+    }
 }
-impl CameraDistortion for Ideal {
-    fn distort(&self, ray: &CameraRay) -> CameraRay {
+impl<T: RealField + Copy> CameraDistortion<T> for Ideal {
+    fn distort(&self, ray: &CameraRay<T>) -> CameraRay<T> {
         *ray
     }
+    fn undistort_with(
+        &self,
+        ray: &CameraRay<T>,
+        _solver: &IterativeSolver,
+    ) -> Result<CameraRay<T>, DistortionError> {
+        Ok(*ray)
+    }
+    /// the domain is the one of the projection
+    fn is_valid_ray(&self, _ray: &CameraRay<T>) -> bool {
+        true
+    }
+}
+
+/// the distortions which stay the same model when the camera frame is rotated or mirrored about the optical
+/// axis along with the image, see `ImageTransform`
+pub trait TransformableDistortion: Sized {
+    /// the coefficients in the transformed camera frame, `None` if the model can not express the distortion
+    /// in that frame
+    fn transform_image(&self, transform: ImageTransform) -> Option<Self>;
+}
+impl TransformableDistortion for Ideal {
+    fn transform_image(&self, _transform: ImageTransform) -> Option<Self> {
+        Some(Ideal {})
+    }
+}
+
+/// settings for inverting a distortion model by iteration
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct IterativeSolver {
+    pub max_iterations: usize,
+    /// the solver stops as soon as re-distorting the estimate is closer than this to the input, for scalar
+    /// types with less precision the tolerance is raised to a few of their epsilons
+    pub tolerance: f64,
+}
+impl Default for IterativeSolver {
+    fn default() -> Self {
+        Self {
+            max_iterations: 100,
+            tolerance: 1e-12,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DistortionError {
+    /// the iterative inversion did not reach the tolerance, `residual` is the remaining error
+    NotConverged { iterations: usize, residual: f64 },
+    /// the distorted ray has no undistorted counterpart in front of the camera
+    OutOfDomain,
+}
+impl fmt::Display for DistortionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DistortionError::NotConverged {
+                iterations,
+                residual,
+            } => write!(
+                f,
+                "undistortion did not converge after {} iterations (residual {:e})",
+                iterations, residual
+            ),
+            DistortionError::OutOfDomain => {
+                write!(f, "the distorted ray is outside of the model's domain")
+            }
+        }
+    }
+}
+impl std::error::Error for DistortionError {}
+
+/// solves `distort(x, y) = target` with newton's method, the jacobian is computed with central differences
+fn solve_newton<T, F>(
+    distort: F,
+    target: (T, T),
+    solver: &IterativeSolver,
+) -> Result<(T, T), DistortionError>
+where
+    T: RealField + Copy,
+    F: Fn(T, T) -> (T, T),
+{
+    let target = Vector2::new(target.0, target.1);
+    let eval = |p: &Vector2<T>| {
+        let (x, y) = distort(p.x, p.y);
+        Vector2::new(x, y)
+    };
+    let h = T::default_epsilon().cbrt();
+    let two = nalgebra::convert::<f64, T>(2.0);
+    let tolerance = tolerance(solver);
+    let mut estimate = target;
+    let mut residual = f64::INFINITY;
+    for iteration in 0..solver.max_iterations {
+        let error = eval(&estimate) - target;
+        let norm = error.norm();
+        // dual numbers only convert to f64 without checking, dropping the derivatives
+        residual = norm.to_subset_unchecked();
+        if !norm.is_finite() {
+            return Err(DistortionError::NotConverged {
+                iterations: iteration,
+                residual,
+            });
+        }
+        if norm < tolerance {
+            return Ok((estimate.x, estimate.y));
+        }
+        let dx = Vector2::new(h, T::zero());
+        let dy = Vector2::new(T::zero(), h);
+        let jacobian = Matrix2::from_columns(&[
+            (eval(&(estimate + dx)) - eval(&(estimate - dx))) / (two * h),
+            (eval(&(estimate + dy)) - eval(&(estimate - dy))) / (two * h),
+        ]);
+        match jacobian.try_inverse() {
+            Some(inverse) => estimate -= inverse * error,
+            None => break,
+        }
+    }
+    Err(DistortionError::NotConverged {
+        iterations: solver.max_iterations,
+        residual,
+    })
+}
+
+/// the tolerance of the solver in `T`, at least a few epsilons of `T`
+fn tolerance<T: RealField + Copy>(solver: &IterativeSolver) -> T {
+    let epsilon = T::default_epsilon() * nalgebra::convert(16.0);
+    nalgebra::convert::<f64, T>(solver.tolerance).max(epsilon)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub struct PlumbBob<T = f64> {
+    pub k1: T,
+    pub k2: T,
+    pub p1: T,
+    pub p2: T,
+    pub k3: T,
+}
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub struct Fisheye<T = f64> {
+    pub k1: T,
+    pub k2: T,
+    pub k3: T,
+    pub k4: T,
+    #[serde(default)]
+    pub s: T,
 }
 
+/// the rational model of OpenCV (`CALIB_RATIONAL_MODEL`), the radial term is a fraction with `k4`, `k5` and `k6`
+/// in the denominator, the tangential terms are the ones of `PlumbBob`
 #[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
-pub struct PlumbBob {
-    k1: f64,
-    k2: f64,
-    p1: f64,
-    p2: f64,
-    k3: f64,
+pub struct Rational {
+    pub k1: f64,
+    pub k2: f64,
+    pub p1: f64,
+    pub p2: f64,
+    pub k3: f64,
+    pub k4: f64,
+    pub k5: f64,
+    pub k6: f64,
 }
+
+/// the full 14 coefficient model of OpenCV, the rational model with the thin prism terms `s1` to `s4`
+/// (`CALIB_THIN_PRISM_MODEL`) followed by a tilt of the sensor by `tau_x` and `tau_y` (`CALIB_TILTED_MODEL`)
 #[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
-pub struct Fisheye {
+pub struct ThinPrism {
     pub k1: f64,
     pub k2: f64,
+    pub p1: f64,
+    pub p2: f64,
     pub k3: f64,
     pub k4: f64,
-    pub s: f64,
+    pub k5: f64,
+    pub k6: f64,
+    pub s1: f64,
+    pub s2: f64,
+    pub s3: f64,
+    pub s4: f64,
+    /// the tilt is optional, files of the 12 coefficient model have none
+    #[serde(default)]
+    pub tau_x: f64,
+    #[serde(default)]
+    pub tau_y: f64,
+}
+
+/// the field of view model of Devernay and Faugeras with the single parameter `omega`, as used by PTAM and
+/// LSD-SLAM, both directions are closed form
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub struct FovDistortion {
+    pub omega: f64,
+}
+
+/// the division model of Fitzgibbon, `x_u = x_d / (1 + lambda1 r_d^2 + lambda2 r_d^4)`
+///
+/// undistortion is a single division, distortion is closed form for the one parameter model (`lambda2 = 0`)
+/// and a few newton steps otherwise. `fit_plumb_bob` and `to_plumb_bob` convert from and to the radial part of
+/// `PlumbBob` by a least squares fit of the radial curves.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub struct Division {
+    pub lambda1: f64,
+    #[serde(default)]
+    pub lambda2: f64,
 }
 
+/// the distortion models which are applied in front of a pinhole projection, like the ones of
+/// `cv::calibrateCamera`, which one is used depends on the available coefficients
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(untagged)]
+pub enum PinholeDistortion {
+    // the richer models have to come first, since unknown fields are ignored
+    ThinPrism(ThinPrism),
+    Rational(Rational),
+    PlumbBob(PlumbBob),
+    Fov(FovDistortion),
+    Division(Division),
+}
+
+/// any of the distortions, selected at runtime by the `type` field of a config, e.g.
+/// `{type = "PlumbBob", coefficients = {k1 = 0.1, k2 = 0.0, p1 = 0.0, p2 = 0.0, k3 = 0.0}}`,
+/// the schema written by `reference/python/compute_calibration.py`
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(tag = "type", content = "coefficients")]
+pub enum AnyDistortion {
+    Ideal,
+    PlumbBob(PlumbBob),
+    Rational(Rational),
+    ThinPrism(ThinPrism),
+    #[serde(alias = "FishEye")]
+    Fisheye(Fisheye),
+    #[serde(alias = "FovDistortion")]
+    Fov(FovDistortion),
+    Division(Division),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct Ideal {}
 
-impl PlumbBob {
-    pub fn new(k1: f64, k2: f64, p1: f64, p2: f64, k3: f64) -> Self {
+impl<T: RealField + Copy> PlumbBob<T> {
+    pub fn new(k1: T, k2: T, p1: T, p2: T, k3: T) -> Self {
         Self { k1, k2, p1, p2, k3 }
     }
-    pub fn params(x: f64, y: f64) -> (f64, f64, f64, f64, f64, f64) {
+    pub fn params(x: T, y: T) -> (T, T, T, T, T, T) {
         let x2 = x * x;
         let y2 = y * y;
         let xy = x * y;
@@ -45,43 +275,678 @@ impl PlumbBob {
         let r6 = r4 * r2;
         (x2, y2, xy, r2, r4, r6)
     }
+    fn distort_xy(&self, x: T, y: T) -> (T, T) {
+        let two = nalgebra::convert::<f64, T>(2.0);
+        let (x2, y2, xy, r2, r4, r6) = PlumbBob::params(x, y);
+        let radial = T::one() + self.k1 * r2 + self.k2 * r4 + self.k3 * r6;
+        let tangential_x = two * self.p1 * xy + self.p2 * (r2 + two * x2);
+        let tangential_y = self.p1 * (r2 + two * y2) + two * self.p2 * xy;
+        (radial * x + tangential_x, radial * y + tangential_y)
+    }
 }
-impl Fisheye {
-    pub fn params(x: f64, y: f64) -> (f64, f64, f64, f64, f64, f64, f64, f64) {
+impl<T: RealField + Copy> Fisheye<T> {
+    /// the angle `theta` to the optical axis is `atan2(r, z)`, which is defined for rays behind the camera too
+    pub fn params(x: T, y: T, z: T) -> (T, T, T, T, T, T, T, T) {
         let r2 = x * x + y * y;
         let r = r2.sqrt();
-        let theta = r.atan();
+        let theta = r.atan2(z);
         let theta2 = theta * theta;
         let theta4 = theta2 * theta2;
         let theta6 = theta4 * theta2;
         let theta8 = theta6 * theta2;
         (r, theta, theta2, theta4, theta6, theta8, x, y)
     }
+    /// solves `theta_d = theta * (1 + k1 theta^2 + k2 theta^4 + k3 theta^6 + k4 theta^8)` for theta
+    fn solve_theta(&self, theta_d: T, solver: &IterativeSolver) -> Result<T, DistortionError> {
+        let c = nalgebra::convert::<f64, T>;
+        let tolerance = tolerance(solver);
+        let mut theta = theta_d;
+        let mut residual = f64::INFINITY;
+        for iteration in 0..solver.max_iterations {
+            let theta2 = theta * theta;
+            let theta4 = theta2 * theta2;
+            let theta6 = theta4 * theta2;
+            let theta8 = theta6 * theta2;
+            let error = theta
+                * (T::one()
+                    + self.k1 * theta2
+                    + self.k2 * theta4
+                    + self.k3 * theta6
+                    + self.k4 * theta8)
+                - theta_d;
+            residual = error.abs().to_subset_unchecked();
+            if !error.is_finite() {
+                return Err(DistortionError::NotConverged {
+                    iterations: iteration,
+                    residual,
+                });
+            }
+            if error.abs() < tolerance {
+                return Ok(theta);
+            }
+            let derivative = T::one()
+                + c(3.0) * self.k1 * theta2
+                + c(5.0) * self.k2 * theta4
+                + c(7.0) * self.k3 * theta6
+                + c(9.0) * self.k4 * theta8;
+            theta -= error / derivative;
+        }
+        Err(DistortionError::NotConverged {
+            iterations: solver.max_iterations,
+            residual,
+        })
+    }
 }
 
-impl CameraDistortion for PlumbBob {
-    fn distort(&self, ray: &CameraRay) -> CameraRay {
-        let (x, y) = ray.xy();
-        let (x2, y2, xy, r2, r4, r6) = PlumbBob::params(x, y);
-        let radial = 1.0 + self.k1 * r2 + self.k2 * r4 + self.k3 * r6;
-        let tangential_x = 2.0 * self.p1 * xy + self.p2 * (r2 + 2.0 * x2);
-        let tangential_y = self.p1 * (r2 + 2.0 * y2) + 2.0 * self.p2 * xy;
-        CameraRay::new(radial * x + tangential_x, radial * y + tangential_y, 1.)
+impl<T: RealField + Copy> CameraDistortion<T> for PlumbBob<T> {
+    fn distort(&self, ray: &CameraRay<T>) -> CameraRay<T> {
+        let (x, y) = ray.plane_xy();
+        let (xd, yd) = self.distort_xy(x, y);
+        CameraRay::new(xd, yd, T::one())
+    }
+    fn undistort_with(
+        &self,
+        ray: &CameraRay<T>,
+        solver: &IterativeSolver,
+    ) -> Result<CameraRay<T>, DistortionError> {
+        let (x, y) = solve_newton(|x, y| self.distort_xy(x, y), ray.plane_xy(), solver)?;
+        Ok(CameraRay::new(x, y, T::one()))
     }
 }
 
-impl CameraDistortion for Fisheye {
+impl<T: RealField + Copy> CameraDistortion<T> for Fisheye<T> {
     // fromhttps://docs.opencv.org/3.4/db/d58/group__calib3d__fisheye.html
     //   a=x/z and b=y/zr2=a2+b2θ=atan(r)
     /// the implementation is inspired from https://docs.opencv.org/3.4/db/d58/group__calib3d__fisheye.html
     /// but the opencv formulation would not supported cameras with more then 180° fov. We implement it instead with atan2 to allow for that.
-    fn distort(&self, ray: &CameraRay) -> CameraRay {
-        let (x, y) = ray.xy();
-        let (r, theta, theta2, theta4, theta6, theta8, x, y) = Self::params(x, y);
+    fn distort(&self, ray: &CameraRay<T>) -> CameraRay<T> {
+        let (x, y, z) = (ray.vector.x, ray.vector.y, ray.vector.z);
+        let (r, theta, theta2, theta4, theta6, theta8, x, y) = Self::params(x, y, z);
+        if r < T::default_epsilon() {
+            return CameraRay::new(x / z, y / z, T::one());
+        }
         let theta_d_r = theta
-            * (1.0 + self.k1 * theta2 + self.k2 * theta4 + self.k3 * theta6 + self.k4 * theta8)
+            * (T::one()
+                + self.k1 * theta2
+                + self.k2 * theta4
+                + self.k3 * theta6
+                + self.k4 * theta8)
             / r;
-        CameraRay::new(theta_d_r * x, theta_d_r * y, 1.)
+        CameraRay::new(theta_d_r * x, theta_d_r * y, T::one())
+    }
+    fn undistort_with(
+        &self,
+        ray: &CameraRay<T>,
+        solver: &IterativeSolver,
+    ) -> Result<CameraRay<T>, DistortionError> {
+        let (x, y) = ray.xy();
+        let theta_d = (x * x + y * y).sqrt();
+        if theta_d < T::default_epsilon() {
+            return Ok(*ray);
+        }
+        let theta = self.solve_theta(theta_d, solver)?;
+        if theta < T::zero() || theta > T::pi() {
+            return Err(DistortionError::OutOfDomain);
+        }
+        // the direction instead of the point on the image plane, which does not exist beyond 90°
+        let (sin, cos) = theta.sin_cos();
+        let scale = sin / theta_d;
+        Ok(CameraRay::new(scale * x, scale * y, cos))
+    }
+    /// all rays except the one straight backwards, which has no direction in the image
+    fn is_valid_ray(&self, ray: &CameraRay<T>) -> bool {
+        ray.vector.z > T::zero() || !ray.vector.x.is_zero() || !ray.vector.y.is_zero()
+    }
+}
+
+/// the radius on the image plane where the denominator `1 + k4 r^2 + k5 r^4 + k6 r^6` of the rational
+/// models first reaches zero, infinite if it stays positive
+fn rational_max_radius(k4: f64, k5: f64, k6: f64) -> f64 {
+    let coefficients = [1.0, k4, k5, k6];
+    let degree = coefficients.iter().rposition(|c| *c != 0.0).unwrap_or(0);
+    if degree == 0 {
+        return f64::INFINITY;
+    }
+    // the roots in r^2 are the eigenvalues of the companion matrix
+    let companion = DMatrix::from_fn(degree, degree, |row, col| {
+        if col == degree - 1 {
+            -coefficients[row] / coefficients[degree]
+        } else if row == col + 1 {
+            1.0
+        } else {
+            0.0
+        }
+    });
+    companion
+        .complex_eigenvalues()
+        .iter()
+        .filter(|root| root.re > 0.0 && root.im.abs() <= 1e-9 * root.re)
+        .map(|root| root.re.sqrt())
+        .fold(f64::INFINITY, f64::min)
+}
+
+impl Rational {
+    /// the rays inside of this radius on the image plane have a positive denominator
+    pub fn max_radius(&self) -> f64 {
+        rational_max_radius(self.k4, self.k5, self.k6)
+    }
+    fn distort_xy(&self, x: f64, y: f64) -> (f64, f64) {
+        let (x2, y2, xy, r2, r4, r6) = PlumbBob::params(x, y);
+        let radial = (1.0 + self.k1 * r2 + self.k2 * r4 + self.k3 * r6)
+            / (1.0 + self.k4 * r2 + self.k5 * r4 + self.k6 * r6);
+        let tangential_x = 2.0 * self.p1 * xy + self.p2 * (r2 + 2.0 * x2);
+        let tangential_y = self.p1 * (r2 + 2.0 * y2) + 2.0 * self.p2 * xy;
+        (radial * x + tangential_x, radial * y + tangential_y)
+    }
+}
+/// the tangential terms are `2 (p . q) q + r^2 p` for `p = (p2, p1)`, so `p` turns with the frame, the radial
+/// terms are invariant
+fn transform_tangential(p1: f64, p2: f64, transform: ImageTransform) -> (f64, f64) {
+    let p = transform.matrix() * Vector2::new(p2, p1);
+    (p.y, p.x)
+}
+impl TransformableDistortion for PlumbBob {
+    fn transform_image(&self, transform: ImageTransform) -> Option<Self> {
+        let (p1, p2) = transform_tangential(self.p1, self.p2, transform);
+        Some(Self { p1, p2, ..*self })
+    }
+}
+impl TransformableDistortion for Rational {
+    fn transform_image(&self, transform: ImageTransform) -> Option<Self> {
+        let (p1, p2) = transform_tangential(self.p1, self.p2, transform);
+        Some(Self { p1, p2, ..*self })
+    }
+}
+/// the fisheye distortion is radially symmetric
+impl TransformableDistortion for Fisheye {
+    fn transform_image(&self, _transform: ImageTransform) -> Option<Self> {
+        Some(*self)
+    }
+}
+
+impl From<PlumbBob> for Rational {
+    fn from(PlumbBob { k1, k2, p1, p2, k3 }: PlumbBob) -> Self {
+        Self {
+            k1,
+            k2,
+            p1,
+            p2,
+            k3,
+            k4: 0.0,
+            k5: 0.0,
+            k6: 0.0,
+        }
+    }
+}
+
+impl CameraDistortion for Rational {
+    fn distort(&self, ray: &CameraRay) -> CameraRay {
+        let (x, y) = ray.plane_xy();
+        let (xd, yd) = self.distort_xy(x, y);
+        CameraRay::new(xd, yd, 1.)
+    }
+    fn undistort_with(
+        &self,
+        ray: &CameraRay,
+        solver: &IterativeSolver,
+    ) -> Result<CameraRay, DistortionError> {
+        let (x, y) = solve_newton(|x, y| self.distort_xy(x, y), ray.plane_xy(), solver)?;
+        Ok(CameraRay::new(x, y, 1.))
+    }
+    /// the rays in front of the camera inside of the pole of the radial factor
+    fn is_valid_ray(&self, ray: &CameraRay) -> bool {
+        let (x, y) = ray.plane_xy();
+        ray.vector.z > 0.0 && x.hypot(y) < self.max_radius()
+    }
+}
+
+impl ThinPrism {
+    /// the projective transformation of the tilted sensor, as in `cv::detail::computeTiltProjectionMatrix`
+    pub fn tilt(&self) -> Matrix3<f64> {
+        let (sin_x, cos_x) = self.tau_x.sin_cos();
+        let (sin_y, cos_y) = self.tau_y.sin_cos();
+        let rot_x = Matrix3::new(1.0, 0.0, 0.0, 0.0, cos_x, sin_x, 0.0, -sin_x, cos_x);
+        let rot_y = Matrix3::new(cos_y, 0.0, -sin_y, 0.0, 1.0, 0.0, sin_y, 0.0, cos_y);
+        let rot = rot_y * rot_x;
+        let proj_z = Matrix3::new(
+            rot[(2, 2)],
+            0.0,
+            -rot[(0, 2)],
+            0.0,
+            rot[(2, 2)],
+            -rot[(1, 2)],
+            0.0,
+            0.0,
+            1.0,
+        );
+        proj_z * rot
+    }
+    fn distort_xy(&self, x: f64, y: f64, tilt: &Matrix3<f64>) -> (f64, f64) {
+        let (x2, y2, xy, r2, r4, r6) = PlumbBob::params(x, y);
+        let radial = (1.0 + self.k1 * r2 + self.k2 * r4 + self.k3 * r6)
+            / (1.0 + self.k4 * r2 + self.k5 * r4 + self.k6 * r6);
+        let xd = radial * x
+            + 2.0 * self.p1 * xy
+            + self.p2 * (r2 + 2.0 * x2)
+            + self.s1 * r2
+            + self.s2 * r4;
+        let yd = radial * y
+            + self.p1 * (r2 + 2.0 * y2)
+            + 2.0 * self.p2 * xy
+            + self.s3 * r2
+            + self.s4 * r4;
+        let tilted = tilt * Vector3::new(xd, yd, 1.0);
+        let scale = if tilted.z != 0.0 { 1.0 / tilted.z } else { 1.0 };
+        (scale * tilted.x, scale * tilted.y)
+    }
+}
+/// the prism terms `r^2 (s1, s3) + r^4 (s2, s4)` turn with the frame. the tilt `(tau_x, tau_y)` is a rotation
+/// vector, which turns as well but changes its sign under mirroring. as OpenCV tilts about y after x, a quarter
+/// turn which exchanges the axes is only possible if the sensor is tilted about one axis at most
+impl TransformableDistortion for ThinPrism {
+    fn transform_image(&self, transform: ImageTransform) -> Option<Self> {
+        let m = transform.matrix();
+        let quarter_turn = m[(0, 0)] == 0.0;
+        if quarter_turn && self.tau_x != 0.0 && self.tau_y != 0.0 {
+            return None;
+        }
+        let (p1, p2) = transform_tangential(self.p1, self.p2, transform);
+        let s13 = m * Vector2::new(self.s1, self.s3);
+        let s24 = m * Vector2::new(self.s2, self.s4);
+        let tau = m.determinant() * m * Vector2::new(self.tau_x, self.tau_y);
+        Some(ThinPrism {
+            s1: s13.x,
+            s2: s24.x,
+            s3: s13.y,
+            s4: s24.y,
+            tau_x: tau.x,
+            tau_y: tau.y,
+            p1,
+            p2,
+            ..*self
+        })
+    }
+}
+impl From<Rational> for ThinPrism {
+    fn from(r: Rational) -> Self {
+        Self {
+            k1: r.k1,
+            k2: r.k2,
+            p1: r.p1,
+            p2: r.p2,
+            k3: r.k3,
+            k4: r.k4,
+            k5: r.k5,
+            k6: r.k6,
+            s1: 0.0,
+            s2: 0.0,
+            s3: 0.0,
+            s4: 0.0,
+            tau_x: 0.0,
+            tau_y: 0.0,
+        }
+    }
+}
+
+impl CameraDistortion for ThinPrism {
+    fn distort(&self, ray: &CameraRay) -> CameraRay {
+        let (x, y) = ray.plane_xy();
+        let (xd, yd) = self.distort_xy(x, y, &self.tilt());
+        CameraRay::new(xd, yd, 1.)
+    }
+    fn undistort_with(
+        &self,
+        ray: &CameraRay,
+        solver: &IterativeSolver,
+    ) -> Result<CameraRay, DistortionError> {
+        let tilt = self.tilt();
+        let (x, y) = solve_newton(|x, y| self.distort_xy(x, y, &tilt), ray.plane_xy(), solver)?;
+        Ok(CameraRay::new(x, y, 1.))
+    }
+    /// the rays in front of the camera inside of the pole of the radial factor, like `Rational`
+    fn is_valid_ray(&self, ray: &CameraRay) -> bool {
+        let (x, y) = ray.plane_xy();
+        ray.vector.z > 0.0 && x.hypot(y) < rational_max_radius(self.k4, self.k5, self.k6)
+    }
+}
+
+impl TransformableDistortion for FovDistortion {
+    fn transform_image(&self, _transform: ImageTransform) -> Option<Self> {
+        Some(*self)
+    }
+}
+
+impl FovDistortion {
+    pub fn new(omega: f64) -> Self {
+        Self { omega }
+    }
+}
+
+impl CameraDistortion for FovDistortion {
+    fn distort(&self, ray: &CameraRay) -> CameraRay {
+        let (x, y) = ray.plane_xy();
+        let r = x.hypot(y);
+        if r < f64::EPSILON || self.omega.abs() < f64::EPSILON {
+            return *ray;
+        }
+        let omega = self.omega;
+        let scale = (2.0 * r * (omega / 2.0).tan()).atan() / (omega * r);
+        CameraRay::new(scale * x, scale * y, 1.)
+    }
+    fn undistort_with(
+        &self,
+        ray: &CameraRay,
+        _solver: &IterativeSolver,
+    ) -> Result<CameraRay, DistortionError> {
+        let (x, y) = ray.plane_xy();
+        let r = x.hypot(y);
+        if r < f64::EPSILON || self.omega.abs() < f64::EPSILON {
+            return Ok(*ray);
+        }
+        let omega = self.omega;
+        // beyond this radius the ray would be at or behind 90° from the optical axis
+        if (r * omega).abs() >= std::f64::consts::FRAC_PI_2 {
+            return Err(DistortionError::OutOfDomain);
+        }
+        let scale = (r * omega).tan() / (2.0 * r * (omega / 2.0).tan());
+        Ok(CameraRay::new(scale * x, scale * y, 1.))
+    }
+}
+
+impl Division {
+    pub fn new(lambda1: f64, lambda2: f64) -> Self {
+        Self { lambda1, lambda2 }
+    }
+    pub fn one_parameter(lambda: f64) -> Self {
+        Self::new(lambda, 0.0)
+    }
+    /// the undistorted radius for a distorted radius
+    fn undistorted_radius(&self, r_d: f64) -> f64 {
+        let r2 = r_d * r_d;
+        r_d / (1.0 + self.lambda1 * r2 + self.lambda2 * r2 * r2)
+    }
+    /// the end of the branch of distorted radii starting at the center: either the fold, where the
+    /// undistorted radius is largest, or the pole, where it goes to infinity. infinite without distortion
+    fn branch_end(&self) -> (f64, bool) {
+        let (l1, l2) = (self.lambda1, self.lambda2);
+        // the smallest positive root of `a s^2 + b s + c` in `s = r_d^2`
+        let root = |a: f64, b: f64, c: f64| -> f64 {
+            let roots = if a == 0.0 {
+                [-c / b, f64::NAN]
+            } else {
+                let d = (b * b - 4.0 * a * c).sqrt();
+                [(-b - d) / (2.0 * a), (-b + d) / (2.0 * a)]
+            };
+            roots
+                .into_iter()
+                .filter(|s| *s > 0.0)
+                .fold(f64::INFINITY, f64::min)
+        };
+        // d/dr_d of r_d / (1 + l1 r_d^2 + l2 r_d^4) vanishes at 3 l2 s^2 + l1 s - 1 = 0
+        let fold = root(3.0 * l2, l1, -1.0);
+        let pole = root(l2, l1, 1.0);
+        if fold < pole {
+            (fold.sqrt(), true)
+        } else {
+            (pole.sqrt(), false)
+        }
+    }
+    /// the largest undistorted radius which has a distorted radius, beyond the fold the model is not invertible
+    pub fn max_undistorted_radius(&self) -> f64 {
+        match self.branch_end() {
+            (r_d, true) => self.undistorted_radius(r_d),
+            _ => f64::INFINITY,
+        }
+    }
+    /// the distorted radius for an undistorted radius, `None` beyond the fold
+    pub(crate) fn distorted_radius(&self, r_u: f64) -> Option<f64> {
+        if r_u.is_nan() || r_u > self.max_undistorted_radius() {
+            return None;
+        }
+        // the root of lambda1 r_u r_d^2 - r_d + r_u = 0 which is continuous at lambda1 = 0
+        let r_d = 2.0 * r_u / (1.0 + (1.0 - 4.0 * self.lambda1 * r_u * r_u).max(0.0).sqrt());
+        if self.lambda2 == 0.0 {
+            return Some(r_d);
+        }
+        // newton on the branch, which falls back to bisection when it would leave the bracket
+        let (mut low, mut high) = (0.0, self.branch_end().0);
+        let mut r_d = if r_d.is_finite() && r_d < high {
+            r_d
+        } else {
+            0.5 * high.min(2.0 * r_u)
+        };
+        for _ in 0..100 {
+            let r2 = r_d * r_d;
+            let factor = 1.0 + self.lambda1 * r2 + self.lambda2 * r2 * r2;
+            let error = r_d - r_u * factor;
+            if error.abs() <= f64::EPSILON * r_u {
+                break;
+            }
+            // the undistorted radius grows along the branch, so the error shrinks
+            if error < 0.0 {
+                low = r_d;
+            } else {
+                high = r_d;
+            }
+            let derivative = 1.0 - r_u * (2.0 * self.lambda1 * r_d + 4.0 * self.lambda2 * r2 * r_d);
+            let next = r_d - error / derivative;
+            r_d = if low < next && next < high {
+                next
+            } else {
+                0.5 * (low + high.min(2.0 * r_d.max(r_u)))
+            };
+        }
+        Some(r_d)
+    }
+    /// fits the division model to the radial distortion of `plumb_bob` for normalized radii up to `max_radius`,
+    /// the tangential coefficients are ignored
+    pub fn fit_plumb_bob(plumb_bob: &PlumbBob, max_radius: f64) -> Self {
+        // r_d / r_u - 1 = lambda1 r_d^2 + lambda2 r_d^4 is linear in the parameters
+        let samples = radius_samples(max_radius).map(|r_u| {
+            let r2 = r_u * r_u;
+            let r_d = r_u
+                * (1.0 + plumb_bob.k1 * r2 + plumb_bob.k2 * r2 * r2 + plumb_bob.k3 * r2 * r2 * r2);
+            (r_d, r_d / r_u - 1.0)
+        });
+        let lambda = fit_even_polynomial(samples, 2);
+        Self::new(lambda[0], lambda[1])
+    }
+    /// fits the radial coefficients of `PlumbBob` to this model for distorted radii up to `max_radius`
+    pub fn to_plumb_bob(&self, max_radius: f64) -> PlumbBob {
+        // r_d / r_u - 1 = k1 r_u^2 + k2 r_u^4 + k3 r_u^6 is linear in the parameters
+        let samples = radius_samples(max_radius).map(|r_d| {
+            let r_u = self.undistorted_radius(r_d);
+            (r_u, r_d / r_u - 1.0)
+        });
+        let k = fit_even_polynomial(samples, 3);
+        PlumbBob::new(k[0], k[1], 0.0, 0.0, k[2])
+    }
+}
+
+fn radius_samples(max_radius: f64) -> impl Iterator<Item = f64> {
+    const SAMPLES: usize = 100;
+    (1..=SAMPLES).map(move |i| max_radius * i as f64 / SAMPLES as f64)
+}
+
+/// least squares fit of `y = c1 r^2 + c2 r^4 + ...` to the samples `(r, y)`
+fn fit_even_polynomial(samples: impl Iterator<Item = (f64, f64)>, degree: usize) -> DVector<f64> {
+    let samples: Vec<(f64, f64)> = samples.collect();
+    let a = DMatrix::from_fn(samples.len(), degree, |i, j| {
+        samples[i].0.powi(2 * (j as i32 + 1))
+    });
+    let b = DVector::from_iterator(samples.len(), samples.iter().map(|(_, y)| *y));
+    a.svd(true, true)
+        .solve(&b, f64::EPSILON)
+        .expect("svd was computed with u and v")
+}
+
+impl TransformableDistortion for Division {
+    fn transform_image(&self, _transform: ImageTransform) -> Option<Self> {
+        Some(*self)
+    }
+}
+
+impl CameraDistortion for Division {
+    fn distort(&self, ray: &CameraRay) -> CameraRay {
+        let (x, y) = ray.plane_xy();
+        let r_u = x.hypot(y);
+        if r_u < f64::EPSILON {
+            return *ray;
+        }
+        let scale = self.distorted_radius(r_u).unwrap_or(f64::NAN) / r_u;
+        CameraRay::new(scale * x, scale * y, 1.)
+    }
+    /// the rays in front of the camera up to the fold of the model
+    fn is_valid_ray(&self, ray: &CameraRay) -> bool {
+        let (x, y) = ray.plane_xy();
+        ray.vector.z > 0.0 && x.hypot(y) <= self.max_undistorted_radius()
+    }
+    fn undistort_with(
+        &self,
+        ray: &CameraRay,
+        _solver: &IterativeSolver,
+    ) -> Result<CameraRay, DistortionError> {
+        let (x, y) = ray.plane_xy();
+        let r2 = x * x + y * y;
+        let denominator = 1.0 + self.lambda1 * r2 + self.lambda2 * r2 * r2;
+        if denominator <= 0.0 {
+            return Err(DistortionError::OutOfDomain);
+        }
+        Ok(CameraRay::new(x / denominator, y / denominator, 1.))
+    }
+}
+
+impl TransformableDistortion for PinholeDistortion {
+    fn transform_image(&self, transform: ImageTransform) -> Option<Self> {
+        Some(match self {
+            PinholeDistortion::ThinPrism(distortion) => {
+                PinholeDistortion::ThinPrism(distortion.transform_image(transform)?)
+            }
+            PinholeDistortion::Rational(distortion) => {
+                PinholeDistortion::Rational(distortion.transform_image(transform)?)
+            }
+            PinholeDistortion::PlumbBob(distortion) => {
+                PinholeDistortion::PlumbBob(distortion.transform_image(transform)?)
+            }
+            PinholeDistortion::Fov(distortion) => {
+                PinholeDistortion::Fov(distortion.transform_image(transform)?)
+            }
+            PinholeDistortion::Division(distortion) => {
+                PinholeDistortion::Division(distortion.transform_image(transform)?)
+            }
+        })
+    }
+}
+
+impl CameraDistortion for PinholeDistortion {
+    fn distort(&self, ray: &CameraRay) -> CameraRay {
+        match self {
+            PinholeDistortion::ThinPrism(distortion) => distortion.distort(ray),
+            PinholeDistortion::Rational(distortion) => distortion.distort(ray),
+            PinholeDistortion::PlumbBob(distortion) => distortion.distort(ray),
+            PinholeDistortion::Fov(distortion) => distortion.distort(ray),
+            PinholeDistortion::Division(distortion) => distortion.distort(ray),
+        }
+    }
+    fn undistort_with(
+        &self,
+        ray: &CameraRay,
+        solver: &IterativeSolver,
+    ) -> Result<CameraRay, DistortionError> {
+        match self {
+            PinholeDistortion::ThinPrism(distortion) => distortion.undistort_with(ray, solver),
+            PinholeDistortion::Rational(distortion) => distortion.undistort_with(ray, solver),
+            PinholeDistortion::PlumbBob(distortion) => distortion.undistort_with(ray, solver),
+            PinholeDistortion::Fov(distortion) => distortion.undistort_with(ray, solver),
+            PinholeDistortion::Division(distortion) => distortion.undistort_with(ray, solver),
+        }
+    }
+    fn is_valid_ray(&self, ray: &CameraRay) -> bool {
+        match self {
+            PinholeDistortion::ThinPrism(distortion) => distortion.is_valid_ray(ray),
+            PinholeDistortion::Rational(distortion) => distortion.is_valid_ray(ray),
+            PinholeDistortion::PlumbBob(distortion) => distortion.is_valid_ray(ray),
+            PinholeDistortion::Fov(distortion) => distortion.is_valid_ray(ray),
+            PinholeDistortion::Division(distortion) => distortion.is_valid_ray(ray),
+        }
+    }
+}
+
+impl From<PinholeDistortion> for AnyDistortion {
+    fn from(distortion: PinholeDistortion) -> Self {
+        match distortion {
+            PinholeDistortion::ThinPrism(distortion) => AnyDistortion::ThinPrism(distortion),
+            PinholeDistortion::Rational(distortion) => AnyDistortion::Rational(distortion),
+            PinholeDistortion::PlumbBob(distortion) => AnyDistortion::PlumbBob(distortion),
+            PinholeDistortion::Fov(distortion) => AnyDistortion::Fov(distortion),
+            PinholeDistortion::Division(distortion) => AnyDistortion::Division(distortion),
+        }
+    }
+}
+
+impl TransformableDistortion for AnyDistortion {
+    fn transform_image(&self, transform: ImageTransform) -> Option<Self> {
+        Some(match self {
+            AnyDistortion::Ideal => AnyDistortion::Ideal,
+            AnyDistortion::PlumbBob(distortion) => {
+                AnyDistortion::PlumbBob(distortion.transform_image(transform)?)
+            }
+            AnyDistortion::Rational(distortion) => {
+                AnyDistortion::Rational(distortion.transform_image(transform)?)
+            }
+            AnyDistortion::ThinPrism(distortion) => {
+                AnyDistortion::ThinPrism(distortion.transform_image(transform)?)
+            }
+            AnyDistortion::Fisheye(distortion) => {
+                AnyDistortion::Fisheye(distortion.transform_image(transform)?)
+            }
+            AnyDistortion::Fov(distortion) => {
+                AnyDistortion::Fov(distortion.transform_image(transform)?)
+            }
+            AnyDistortion::Division(distortion) => {
+                AnyDistortion::Division(distortion.transform_image(transform)?)
+            }
+        })
+    }
+}
+
+impl CameraDistortion for AnyDistortion {
+    fn distort(&self, ray: &CameraRay) -> CameraRay {
+        match self {
+            AnyDistortion::Ideal => Ideal {}.distort(ray),
+            AnyDistortion::PlumbBob(distortion) => distortion.distort(ray),
+            AnyDistortion::Rational(distortion) => distortion.distort(ray),
+            AnyDistortion::ThinPrism(distortion) => distortion.distort(ray),
+            AnyDistortion::Fisheye(distortion) => distortion.distort(ray),
+            AnyDistortion::Fov(distortion) => distortion.distort(ray),
+            AnyDistortion::Division(distortion) => distortion.distort(ray),
+        }
+    }
+    fn undistort_with(
+        &self,
+        ray: &CameraRay,
+        solver: &IterativeSolver,
+    ) -> Result<CameraRay, DistortionError> {
+        match self {
+            AnyDistortion::Ideal => Ideal {}.undistort_with(ray, solver),
+            AnyDistortion::PlumbBob(distortion) => distortion.undistort_with(ray, solver),
+            AnyDistortion::Rational(distortion) => distortion.undistort_with(ray, solver),
+            AnyDistortion::ThinPrism(distortion) => distortion.undistort_with(ray, solver),
+            AnyDistortion::Fisheye(distortion) => distortion.undistort_with(ray, solver),
+            AnyDistortion::Fov(distortion) => distortion.undistort_with(ray, solver),
+            AnyDistortion::Division(distortion) => distortion.undistort_with(ray, solver),
+        }
+    }
+    fn is_valid_ray(&self, ray: &CameraRay) -> bool {
+        match self {
+            AnyDistortion::Ideal => Ideal {}.is_valid_ray(ray),
+            AnyDistortion::PlumbBob(distortion) => distortion.is_valid_ray(ray),
+            AnyDistortion::Rational(distortion) => distortion.is_valid_ray(ray),
+            AnyDistortion::ThinPrism(distortion) => distortion.is_valid_ray(ray),
+            AnyDistortion::Fisheye(distortion) => distortion.is_valid_ray(ray),
+            AnyDistortion::Fov(distortion) => distortion.is_valid_ray(ray),
+            AnyDistortion::Division(distortion) => distortion.is_valid_ray(ray),
+        }
     }
 }
 
@@ -103,4 +968,358 @@ mod tests {
         let p3 = p.distort(&p2);
         assert_eq!(p3, CameraRay::new(1.0, 1.0, 1.0));
     }
+
+    const RAYS: [(f64, f64); 6] = [
+        (0.0, 0.0),
+        (0.1, 0.0),
+        (0.0, -0.3),
+        (0.4, 0.3),
+        (-0.6, 0.5),
+        (-0.7, -0.8),
+    ];
+
+    #[test]
+    fn test_plumb_undistort_roundtrip() {
+        use super::*;
+        let p = PlumbBob::new(0.8, -0.3, 0.05, -0.04, 0.2);
+        for (x, y) in RAYS {
+            let src = CameraRay::new(x, y, 1.0);
+            let distorted = p.distort(&src);
+            let dst = p.undistort(&distorted).unwrap();
+            assert_eq!(src, dst);
+        }
+    }
+    #[test]
+    fn test_fisheye_undistort_roundtrip() {
+        use super::*;
+        let p = Fisheye {
+            k1: 0.3,
+            k2: -0.1,
+            k3: 0.05,
+            k4: -0.01,
+            s: 0.0,
+        };
+        for (x, y) in RAYS {
+            let src = CameraRay::new(x, y, 1.0);
+            let distorted = p.distort(&src);
+            let dst = p.undistort(&distorted).unwrap();
+            assert_eq!(src, dst);
+        }
+    }
+    #[test]
+    fn test_fisheye_behind_image_plane() {
+        use super::*;
+        let p = Fisheye {
+            k1: 0.02,
+            k2: -0.003,
+            k3: 0.0005,
+            k4: -0.0001,
+            s: 0.0,
+        };
+        for (x, y, z) in [(1.0, 0.0, 0.0), (0.3, -0.4, -0.1), (-0.5, 0.2, -0.3)] {
+            let src = CameraRay::new(x, y, z);
+            let distorted = p.distort(&src);
+            assert_eq!(distorted.vector.z, 1.0);
+            let dst = p.undistort(&distorted).unwrap();
+            assert_eq!(src, dst);
+            assert!(dst.vector.z < 1e-12);
+        }
+        // no ray is further than 180° from the optical axis
+        let distorted = CameraRay::new(4.0, 0.0, 1.0);
+        assert_eq!(p.undistort(&distorted), Err(DistortionError::OutOfDomain));
+    }
+    #[test]
+    fn test_single_precision_roundtrip() {
+        use super::*;
+        let plumb_bob = PlumbBob::new(0.8f32, -0.3, 0.05, -0.04, 0.2);
+        let fisheye = Fisheye {
+            k1: 0.3f32,
+            k2: -0.1,
+            k3: 0.05,
+            k4: -0.01,
+            s: 0.0,
+        };
+        for (x, y) in RAYS {
+            let src = CameraRay::new(x as f32, y as f32, 1.0);
+            let dst = plumb_bob.undistort(&plumb_bob.distort(&src)).unwrap();
+            assert_eq!(src, dst);
+            let dst = fisheye.undistort(&fisheye.distort(&src)).unwrap();
+            assert_eq!(src, dst);
+        }
+    }
+    #[test]
+    fn test_rational_undistort_roundtrip() {
+        use super::*;
+        let p = Rational {
+            k1: 0.8,
+            k2: -0.3,
+            p1: 0.05,
+            p2: -0.04,
+            k3: 0.2,
+            k4: 0.4,
+            k5: 0.1,
+            k6: -0.02,
+        };
+        for (x, y) in RAYS {
+            let src = CameraRay::new(x, y, 1.0);
+            let distorted = p.distort(&src);
+            let dst = p.undistort(&distorted).unwrap();
+            assert_eq!(src, dst);
+        }
+    }
+    #[test]
+    fn test_rational_reference() {
+        use super::*;
+        // the formula of the OpenCV documentation evaluated for x = 0.5, y = -0.25
+        let p = Rational {
+            k1: 0.1,
+            k2: 0.01,
+            p1: 0.001,
+            p2: -0.002,
+            k3: 0.0001,
+            k4: 0.05,
+            k5: 0.005,
+            k6: 0.0005,
+        };
+        let distorted = p.distort(&CameraRay::new(0.5, -0.25, 1.0));
+        let radial = (1.0 + 0.1 * 0.3125 + 0.01 * 0.09765625 + 0.0001 * 0.030517578125)
+            / (1.0 + 0.05 * 0.3125 + 0.005 * 0.09765625 + 0.0005 * 0.030517578125);
+        let x = 0.5 * radial + 2.0 * 0.001 * -0.125 - 0.002 * (0.3125 + 0.5);
+        let y = -0.25 * radial + 0.001 * (0.3125 + 0.125) + 2.0 * -0.002 * -0.125;
+        assert_eq!(distorted, CameraRay::new(x, y, 1.0));
+
+        let plumb_bob = PlumbBob::new(0.1, 0.01, 0.001, -0.002, 0.0001);
+        let ray = CameraRay::new(0.5, -0.25, 1.0);
+        assert_eq!(
+            Rational::from(plumb_bob).distort(&ray),
+            plumb_bob.distort(&ray)
+        );
+    }
+    const THIN_PRISM: super::ThinPrism = super::ThinPrism {
+        k1: 0.1,
+        k2: 0.01,
+        p1: 0.001,
+        p2: -0.002,
+        k3: 0.0001,
+        k4: 0.05,
+        k5: 0.005,
+        k6: 0.0005,
+        s1: 0.002,
+        s2: -0.0005,
+        s3: -0.001,
+        s4: 0.0003,
+        tau_x: 0.02,
+        tau_y: -0.01,
+    };
+    #[test]
+    fn test_thin_prism_reference() {
+        use super::*;
+        // the table has to match the output of `reference/python/thin_prism_reference.py`,
+        // which runs cv::projectPoints with an identity camera matrix
+        for ((x, y), (xd, yd)) in [
+            ((0.5, -0.25), (0.5066495649869485, -0.25325656169934124)),
+            ((-0.3, 0.4), (-0.305992215305338, 0.40796658481910675)),
+            ((0.0, 0.0), (0.0, 0.0)),
+        ] {
+            let distorted = THIN_PRISM.distort(&CameraRay::new(x, y, 1.0));
+            assert!((distorted.vector.x - xd).abs() < 1e-9);
+            assert!((distorted.vector.y - yd).abs() < 1e-9);
+        }
+    }
+    #[test]
+    fn test_thin_prism_undistort_roundtrip() {
+        use super::*;
+        let p = ThinPrism {
+            s1: 0.05,
+            s3: -0.04,
+            tau_x: 0.1,
+            tau_y: 0.05,
+            ..THIN_PRISM
+        };
+        for (x, y) in RAYS {
+            let src = CameraRay::new(x, y, 1.0);
+            let distorted = p.distort(&src);
+            let dst = p.undistort(&distorted).unwrap();
+            assert_eq!(src, dst);
+        }
+        // without the extra terms the model is the rational model
+        let rational = Rational {
+            k1: 0.1,
+            k2: 0.01,
+            p1: 0.001,
+            p2: -0.002,
+            k3: 0.0001,
+            k4: 0.05,
+            k5: 0.005,
+            k6: 0.0005,
+        };
+        let ray = CameraRay::new(0.5, -0.25, 1.0);
+        assert_eq!(
+            ThinPrism::from(rational).distort(&ray),
+            rational.distort(&ray)
+        );
+    }
+    #[test]
+    fn test_fov() {
+        use super::*;
+        let p = FovDistortion::new(0.9);
+        for (x, y) in RAYS {
+            let src = CameraRay::new(x, y, 1.0);
+            let distorted = p.distort(&src);
+            let dst = p.undistort(&distorted).unwrap();
+            assert_eq!(src, dst);
+        }
+        // r_d = atan(2 r_u tan(omega / 2)) / omega
+        let distorted = FovDistortion::new(1.0).distort(&CameraRay::new(1.0, 0.0, 1.0));
+        assert!((distorted.vector.x - 0.8296227542752249).abs() < 1e-12);
+        assert_eq!(
+            p.undistort(&CameraRay::new(2.0, 0.0, 1.0)),
+            Err(DistortionError::OutOfDomain)
+        );
+    }
+    #[test]
+    fn test_division() {
+        use super::*;
+        for p in [
+            Division::one_parameter(-0.3),
+            Division::one_parameter(0.2),
+            Division::new(-0.3, 0.05),
+        ] {
+            for (x, y) in RAYS {
+                let src = CameraRay::new(x, y, 1.0);
+                let undistorted = p.undistort(&src).unwrap();
+                assert_eq!(p.distort(&undistorted), src);
+            }
+        }
+        // x_u = x_d / (1 + lambda r_d^2)
+        let p = Division::one_parameter(-0.3);
+        let undistorted = p.undistort(&CameraRay::new(0.5, 0.0, 1.0)).unwrap();
+        assert_eq!(
+            undistorted,
+            CameraRay::new(0.5 / (1.0 - 0.3 * 0.25), 0.0, 1.0)
+        );
+        // up to the fold the distortion inverts the undistortion, also for a starting guess beyond it
+        for p in [
+            Division::new(0.5, 0.1),
+            Division::new(0.2, -0.3),
+            Division::new(-0.3, 0.05),
+        ] {
+            let r_max = p.max_undistorted_radius();
+            for r_u in [0.1, 0.4, 0.6, 0.9, 1.5] {
+                let ray = CameraRay::new(r_u, 0.0, 1.0);
+                assert_eq!(p.is_valid_ray(&ray), r_u <= r_max);
+                if p.is_valid_ray(&ray) {
+                    let distorted = p.distort(&ray);
+                    assert!((p.undistort(&distorted).unwrap().vector - ray.vector).norm() < 1e-9);
+                }
+            }
+        }
+    }
+    #[test]
+    fn test_division_plumb_bob_fit() {
+        use super::*;
+        let plumb_bob = PlumbBob::new(-0.25, 0.05, 0.0, 0.0, 0.0);
+        let division = Division::fit_plumb_bob(&plumb_bob, 0.8);
+        let refit = division.to_plumb_bob(0.8);
+        for (x, y) in [(0.1, 0.0), (0.3, -0.2), (-0.4, 0.3), (0.0, 0.55)] {
+            let src = CameraRay::new(x, y, 1.0);
+            let expected = plumb_bob.distort(&src).vector;
+            assert!((division.distort(&src).vector - expected).norm() < 1e-3);
+            assert!((refit.distort(&src).vector - expected).norm() < 1e-3);
+        }
+    }
+    #[test]
+    fn test_rational_domain() {
+        use super::*;
+        let mut rational = Rational::from(PlumbBob::new(0.1, 0.0, 0.0, 0.0, 0.0));
+        assert_eq!(rational.max_radius(), f64::INFINITY);
+        // the denominator 1 - r^2 + 0.25 r^4 - 0.02 r^6 first vanishes at r^2 ~ 1.49
+        (rational.k4, rational.k5, rational.k6) = (-1.0, 0.25, -0.02);
+        let r2 = rational.max_radius().powi(2);
+        assert!((1.0 - r2 + 0.25 * r2 * r2 - 0.02 * r2 * r2 * r2).abs() < 1e-9);
+        assert!(rational.is_valid_ray(&CameraRay::new(1.0, 0.0, 1.0)));
+        assert!(!rational.is_valid_ray(&CameraRay::new(1.0, 1.0, 1.0)));
+        assert!(!rational.is_valid_ray(&CameraRay::new(0.1, 0.0, -1.0)));
+        // the enums forward the domain of their model
+        let pinhole = PinholeDistortion::Rational(rational);
+        assert!(!pinhole.is_valid_ray(&CameraRay::new(1.0, 1.0, 1.0)));
+        assert!(!AnyDistortion::from(pinhole).is_valid_ray(&CameraRay::new(1.0, 1.0, 1.0)));
+        let division = PinholeDistortion::Division(Division::one_parameter(0.5));
+        assert!(!division.is_valid_ray(&CameraRay::new(1.0, 0.0, 1.0)));
+        let thin_prism = ThinPrism::from(rational);
+        assert!(!thin_prism.is_valid_ray(&CameraRay::new(1.0, 1.0, 1.0)));
+    }
+    #[test]
+    fn test_pinhole_distortion_selection() {
+        use super::*;
+        let plumb_bob: PinholeDistortion =
+            toml::from_str("k1 = 0.1\nk2 = 0.1\np1 = 0.2\np2 = 0.1\nk3 = 0.0").unwrap();
+        assert_eq!(
+            plumb_bob,
+            PinholeDistortion::PlumbBob(PlumbBob::new(0.1, 0.1, 0.2, 0.1, 0.0))
+        );
+        let rational: PinholeDistortion = toml::from_str(
+            "k1 = 0.1\nk2 = 0.1\np1 = 0.2\np2 = 0.1\nk3 = 0.0\nk4 = 0.3\nk5 = 0.0\nk6 = 0.0",
+        )
+        .unwrap();
+        assert!(matches!(rational, PinholeDistortion::Rational(r) if r.k4 == 0.3));
+        let thin_prism: PinholeDistortion = toml::from_str(
+            "k1 = 0.1\nk2 = 0.1\np1 = 0.2\np2 = 0.1\nk3 = 0.0\nk4 = 0.3\nk5 = 0.0\nk6 = 0.0\ns1 = 0.01\ns2 = 0.0\ns3 = 0.0\ns4 = 0.0",
+        )
+        .unwrap();
+        assert!(
+            matches!(thin_prism, PinholeDistortion::ThinPrism(t) if t.s1 == 0.01 && t.tau_x == 0.0)
+        );
+        let fov: PinholeDistortion = toml::from_str("omega = 0.9").unwrap();
+        assert_eq!(fov, PinholeDistortion::Fov(FovDistortion::new(0.9)));
+        let division: PinholeDistortion = toml::from_str("lambda1 = -0.2").unwrap();
+        assert_eq!(
+            division,
+            PinholeDistortion::Division(Division::one_parameter(-0.2))
+        );
+    }
+    #[test]
+    fn test_any_distortion() {
+        use super::*;
+        // the distortion of the example config in the root of the repository
+        #[derive(Deserialize)]
+        struct Config {
+            distortion: AnyDistortion,
+        }
+        let config: Config = toml::from_str(include_str!("../config.toml")).unwrap();
+        let plumb_bob = config.distortion;
+        let expected = PlumbBob::new(0.1, 0.1, 0.2, 0.1, 0.0);
+        assert_eq!(plumb_bob, AnyDistortion::PlumbBob(expected));
+        let src = CameraRay::new(0.3, -0.2, 1.0);
+        assert_eq!(plumb_bob.distort(&src), expected.distort(&src));
+        // the fisheye of the python script has no skew
+        let fisheye: AnyDistortion = toml::from_str(
+            "type = \"FishEye\"\ncoefficients = {k1 = 0.1, k2 = 0.01, k3 = 0.0, k4 = 0.0}",
+        )
+        .unwrap();
+        assert!(matches!(fisheye, AnyDistortion::Fisheye(f) if f.k2 == 0.01 && f.s == 0.0));
+        let ideal: AnyDistortion = toml::from_str("type = \"Ideal\"").unwrap();
+        assert_eq!(ideal.undistort(&src).unwrap(), src);
+        assert!(toml::from_str::<AnyDistortion>("type = \"PlumbBob\"").is_err());
+    }
+    #[test]
+    fn test_ideal_undistort() {
+        use super::*;
+        let src = CameraRay::new(0.3, -0.2, 1.0);
+        assert_eq!(Ideal {}.undistort(&src).unwrap(), src);
+    }
+    #[test]
+    fn test_undistort_not_converged() {
+        use super::*;
+        let p = PlumbBob::new(0.8, -0.3, 0.05, -0.04, 0.2);
+        let solver = IterativeSolver {
+            max_iterations: 1,
+            tolerance: 1e-12,
+        };
+        let distorted = p.distort(&CameraRay::new(0.4, 0.3, 1.0));
+        assert!(matches!(
+            p.undistort_with(&distorted, &solver),
+            Err(DistortionError::NotConverged { iterations: 1, .. })
+        ));
+    }
 }
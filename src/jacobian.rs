@@ -0,0 +1,1236 @@
+//! analytic jacobians of the projections and distortions, e.g. for bundle adjustment
+//!
+//! the parameters are ordered like the fields of the models, the jacobians of a `CameraModel` have the
+//! projection parameters in front of the distortion coefficients.
+use crate::camera::{CameraModel, CameraRay};
+use crate::distortion::{
+    AnyDistortion, CameraDistortion, Division, Fisheye, FovDistortion, Ideal, PinholeDistortion,
+    PlumbBob, Rational, ThinPrism,
+};
+use crate::projection;
+use crate::projection::{
+    AnyProjection, BSplineCamera, CameraProjection, DoubleSphere, Equisolid, ExtendedUnifiedCamera,
+    Orthographic, Pinhole, Scaramuzza, Stereographic, UnifiedCamera,
+};
+
+use nalgebra::{DMatrix, Matrix2, Matrix2x3, Matrix3, Matrix3x2, Vector2, Vector3};
+
+pub trait ProjectionJacobian: CameraProjection {
+    /// the intrinsic parameters, in the order of the columns of `parameter_jacobian`
+    fn parameters(&self) -> Vec<f64>;
+    fn set_parameters(&mut self, parameters: &[f64]);
+    /// the derivative of the pixel with respect to the ray
+    fn ray_jacobian(&self, ray: &CameraRay) -> Matrix2x3<f64>;
+    /// the derivative of the pixel with respect to the parameters, a `2 x n` matrix
+    fn parameter_jacobian(&self, ray: &CameraRay) -> DMatrix<f64>;
+}
+
+pub trait DistortionJacobian: CameraDistortion {
+    /// the coefficients, in the order of the columns of `parameter_jacobian`
+    fn parameters(&self) -> Vec<f64>;
+    fn set_parameters(&mut self, parameters: &[f64]);
+    /// the derivative of the distorted ray with respect to the ray
+    fn ray_jacobian(&self, ray: &CameraRay) -> Matrix3<f64>;
+    /// the derivative of the distorted ray with respect to the coefficients, a `3 x n` matrix
+    fn parameter_jacobian(&self, ray: &CameraRay) -> DMatrix<f64>;
+}
+
+impl<T, V> CameraModel<T, V>
+where
+    T: ProjectionJacobian,
+    V: DistortionJacobian,
+{
+    /// the derivative of the pixel with respect to the ray
+    pub fn ray_jacobian(&self, ray: &CameraRay) -> Matrix2x3<f64> {
+        let distorted = self.distortion().distort(ray);
+        self.projection().ray_jacobian(&distorted) * self.distortion().ray_jacobian(ray)
+    }
+    /// the derivative of the pixel with respect to the projection parameters and the distortion coefficients
+    pub fn parameter_jacobian(&self, ray: &CameraRay) -> DMatrix<f64> {
+        let distorted = self.distortion().distort(ray);
+        let projection = self.projection().parameter_jacobian(&distorted);
+        let distortion =
+            self.projection().ray_jacobian(&distorted) * self.distortion().parameter_jacobian(ray);
+        let (np, nd) = (projection.ncols(), distortion.ncols());
+        let mut jacobian = DMatrix::zeros(2, np + nd);
+        jacobian.columns_mut(0, np).copy_from(&projection);
+        jacobian.columns_mut(np, nd).copy_from(&distortion);
+        jacobian
+    }
+}
+
+fn take<const N: usize>(parameters: &[f64]) -> [f64; N] {
+    parameters.try_into().expect("wrong number of parameters")
+}
+
+/// the derivative of `(fx x + skew y + cx, fy y + cy)` with respect to `fx, fy, cx, cy, skew`
+fn affine_parameter_jacobian(x: f64, y: f64) -> DMatrix<f64> {
+    DMatrix::from_row_slice(2, 5, &[x, 0.0, 1.0, 0.0, y, 0.0, y, 0.0, 1.0, 0.0])
+}
+
+/// the point `g(theta) (cos phi, sin phi)` of a radial projection and its derivative with respect to the ray,
+/// `g` returns the radius and its derivative for the angle to the optical axis
+fn radial(ray: &CameraRay, g: impl Fn(f64) -> (f64, f64)) -> (Vector2<f64>, Matrix2x3<f64>) {
+    let v = ray.vector;
+    let r = v.x.hypot(v.y);
+    if r < f64::EPSILON {
+        // all of the radial projections behave like `g(theta) = theta` close to the axis
+        let jacobian = Matrix2x3::new(1.0 / v.z, 0.0, 0.0, 0.0, 1.0 / v.z, 0.0);
+        return (Vector2::zeros(), jacobian);
+    }
+    let n2 = v.norm_squared();
+    let (radius, derivative) = g(r.atan2(v.z));
+    let direction = Vector2::new(v.x / r, v.y / r);
+    let dtheta = Vector3::new(v.z * v.x / (r * n2), v.z * v.y / (r * n2), -r / n2);
+    let r3 = r * r * r;
+    let ddirection = Matrix2x3::new(
+        v.y * v.y / r3,
+        -v.x * v.y / r3,
+        0.0,
+        -v.x * v.y / r3,
+        v.x * v.x / r3,
+        0.0,
+    );
+    (
+        direction * radius,
+        direction * dtheta.transpose() * derivative + ddirection * radius,
+    )
+}
+
+/// the jacobians of the radial projections with the intrinsics `fx, fy, cx, cy, skew`
+fn radial_jacobians(
+    (fx, fy, skew): (f64, f64, f64),
+    ray: &CameraRay,
+    g: impl Fn(f64) -> (f64, f64),
+) -> (Matrix2x3<f64>, DMatrix<f64>) {
+    let (point, jacobian) = radial(ray, g);
+    (
+        Matrix2::new(fx, skew, 0.0, fy) * jacobian,
+        affine_parameter_jacobian(point.x, point.y),
+    )
+}
+
+impl ProjectionJacobian for Pinhole {
+    fn parameters(&self) -> Vec<f64> {
+        vec![self.fx, self.fy, self.cx, self.cy, self.skew]
+    }
+    fn set_parameters(&mut self, parameters: &[f64]) {
+        [self.fx, self.fy, self.cx, self.cy, self.skew] = take(parameters);
+    }
+    /// the derivative of the affine map times the one of the division by `z`
+    fn ray_jacobian(&self, ray: &CameraRay) -> Matrix2x3<f64> {
+        let z = ray.vector.z;
+        let (x, y) = (ray.vector.x / z, ray.vector.y / z);
+        let division = Matrix2x3::new(1.0 / z, 0.0, -x / z, 0.0, 1.0 / z, -y / z);
+        Matrix2::new(self.fx, self.skew, 0.0, self.fy) * division
+    }
+    fn parameter_jacobian(&self, ray: &CameraRay) -> DMatrix<f64> {
+        let z = ray.vector.z;
+        affine_parameter_jacobian(ray.vector.x / z, ray.vector.y / z)
+    }
+}
+
+impl ProjectionJacobian for projection::Fisheye {
+    fn parameters(&self) -> Vec<f64> {
+        vec![self.fx, self.fy, self.cx, self.cy, self.skew]
+    }
+    fn set_parameters(&mut self, parameters: &[f64]) {
+        [self.fx, self.fy, self.cx, self.cy, self.skew] = take(parameters);
+    }
+    fn ray_jacobian(&self, ray: &CameraRay) -> Matrix2x3<f64> {
+        radial_jacobians((self.fx, self.fy, self.skew), ray, |theta| (theta, 1.0)).0
+    }
+    fn parameter_jacobian(&self, ray: &CameraRay) -> DMatrix<f64> {
+        radial_jacobians((self.fx, self.fy, self.skew), ray, |theta| (theta, 1.0)).1
+    }
+}
+
+fn stereographic(theta: f64) -> (f64, f64) {
+    let (sin, cos) = (theta / 2.0).sin_cos();
+    (2.0 * sin / cos, 1.0 / (cos * cos))
+}
+
+impl ProjectionJacobian for Stereographic {
+    fn parameters(&self) -> Vec<f64> {
+        vec![self.fx, self.fy, self.cx, self.cy, self.skew]
+    }
+    fn set_parameters(&mut self, parameters: &[f64]) {
+        [self.fx, self.fy, self.cx, self.cy, self.skew] = take(parameters);
+    }
+    fn ray_jacobian(&self, ray: &CameraRay) -> Matrix2x3<f64> {
+        radial_jacobians((self.fx, self.fy, self.skew), ray, stereographic).0
+    }
+    fn parameter_jacobian(&self, ray: &CameraRay) -> DMatrix<f64> {
+        radial_jacobians((self.fx, self.fy, self.skew), ray, stereographic).1
+    }
+}
+
+fn equisolid(theta: f64) -> (f64, f64) {
+    let (sin, cos) = (theta / 2.0).sin_cos();
+    (2.0 * sin, cos)
+}
+
+impl ProjectionJacobian for Equisolid {
+    fn parameters(&self) -> Vec<f64> {
+        vec![self.fx, self.fy, self.cx, self.cy, self.skew]
+    }
+    fn set_parameters(&mut self, parameters: &[f64]) {
+        [self.fx, self.fy, self.cx, self.cy, self.skew] = take(parameters);
+    }
+    fn ray_jacobian(&self, ray: &CameraRay) -> Matrix2x3<f64> {
+        radial_jacobians((self.fx, self.fy, self.skew), ray, equisolid).0
+    }
+    fn parameter_jacobian(&self, ray: &CameraRay) -> DMatrix<f64> {
+        radial_jacobians((self.fx, self.fy, self.skew), ray, equisolid).1
+    }
+}
+
+fn orthographic(theta: f64) -> (f64, f64) {
+    theta.sin_cos()
+}
+
+impl ProjectionJacobian for Orthographic {
+    fn parameters(&self) -> Vec<f64> {
+        vec![self.fx, self.fy, self.cx, self.cy, self.skew]
+    }
+    fn set_parameters(&mut self, parameters: &[f64]) {
+        [self.fx, self.fy, self.cx, self.cy, self.skew] = take(parameters);
+    }
+    fn ray_jacobian(&self, ray: &CameraRay) -> Matrix2x3<f64> {
+        radial_jacobians((self.fx, self.fy, self.skew), ray, orthographic).0
+    }
+    fn parameter_jacobian(&self, ray: &CameraRay) -> DMatrix<f64> {
+        radial_jacobians((self.fx, self.fy, self.skew), ray, orthographic).1
+    }
+}
+
+/// the denominator `d` of `(fx x / d + cx, fy y / d + cy)`, its derivative with respect to the ray and with
+/// respect to the parameters behind `fx, fy, cx, cy`
+type Denominator = (f64, Vector3<f64>, Vec<f64>);
+
+fn central_ray_jacobian(
+    fx: f64,
+    fy: f64,
+    ray: &CameraRay,
+    (d, dd, _): &Denominator,
+) -> Matrix2x3<f64> {
+    let (x, y) = ray.xy();
+    let mut jacobian = Matrix2x3::zeros();
+    jacobian.set_row(0, &(-fx * x / (d * d) * dd.transpose()));
+    jacobian.set_row(1, &(-fy * y / (d * d) * dd.transpose()));
+    jacobian[(0, 0)] += fx / d;
+    jacobian[(1, 1)] += fy / d;
+    jacobian
+}
+
+fn central_parameter_jacobian(
+    fx: f64,
+    fy: f64,
+    ray: &CameraRay,
+    (d, _, dp): &Denominator,
+) -> DMatrix<f64> {
+    let (x, y) = ray.xy();
+    let mut jacobian = DMatrix::zeros(2, 4 + dp.len());
+    jacobian[(0, 0)] = x / d;
+    jacobian[(1, 1)] = y / d;
+    jacobian[(0, 2)] = 1.0;
+    jacobian[(1, 3)] = 1.0;
+    for (i, dp) in dp.iter().enumerate() {
+        jacobian[(0, 4 + i)] = -fx * x / (d * d) * dp;
+        jacobian[(1, 4 + i)] = -fy * y / (d * d) * dp;
+    }
+    jacobian
+}
+
+impl UnifiedCamera {
+    fn denominator(&self, ray: &CameraRay) -> Denominator {
+        let v = ray.vector;
+        let norm = v.norm();
+        (
+            v.z + self.xi * norm,
+            self.xi * v / norm + Vector3::z(),
+            vec![norm],
+        )
+    }
+}
+
+impl ProjectionJacobian for UnifiedCamera {
+    fn parameters(&self) -> Vec<f64> {
+        vec![self.fx, self.fy, self.cx, self.cy, self.xi]
+    }
+    fn set_parameters(&mut self, parameters: &[f64]) {
+        [self.fx, self.fy, self.cx, self.cy, self.xi] = take(parameters);
+    }
+    fn ray_jacobian(&self, ray: &CameraRay) -> Matrix2x3<f64> {
+        central_ray_jacobian(self.fx, self.fy, ray, &self.denominator(ray))
+    }
+    fn parameter_jacobian(&self, ray: &CameraRay) -> DMatrix<f64> {
+        central_parameter_jacobian(self.fx, self.fy, ray, &self.denominator(ray))
+    }
+}
+
+impl ExtendedUnifiedCamera {
+    fn denominator(&self, ray: &CameraRay) -> Denominator {
+        let ExtendedUnifiedCamera { alpha, beta, .. } = *self;
+        let v = ray.vector;
+        let r2 = v.x * v.x + v.y * v.y;
+        let rho = (beta * r2 + v.z * v.z).sqrt();
+        let drho = Vector3::new(beta * v.x, beta * v.y, v.z) / rho;
+        (
+            alpha * rho + (1.0 - alpha) * v.z,
+            alpha * drho + (1.0 - alpha) * Vector3::z(),
+            vec![rho - v.z, alpha * r2 / (2.0 * rho)],
+        )
+    }
+}
+
+impl ProjectionJacobian for ExtendedUnifiedCamera {
+    fn parameters(&self) -> Vec<f64> {
+        vec![self.fx, self.fy, self.cx, self.cy, self.alpha, self.beta]
+    }
+    fn set_parameters(&mut self, parameters: &[f64]) {
+        [self.fx, self.fy, self.cx, self.cy, self.alpha, self.beta] = take(parameters);
+    }
+    fn ray_jacobian(&self, ray: &CameraRay) -> Matrix2x3<f64> {
+        central_ray_jacobian(self.fx, self.fy, ray, &self.denominator(ray))
+    }
+    fn parameter_jacobian(&self, ray: &CameraRay) -> DMatrix<f64> {
+        central_parameter_jacobian(self.fx, self.fy, ray, &self.denominator(ray))
+    }
+}
+
+impl DoubleSphere {
+    fn denominator(&self, ray: &CameraRay) -> Denominator {
+        let DoubleSphere { xi, alpha, .. } = *self;
+        let v = ray.vector;
+        let d1 = v.norm();
+        let z = xi * d1 + v.z;
+        let d2 = (v.x * v.x + v.y * v.y + z * z).sqrt();
+        let dz = xi * v / d1 + Vector3::z();
+        let dd2 = (Vector3::new(v.x, v.y, 0.0) + z * dz) / d2;
+        (
+            alpha * d2 + (1.0 - alpha) * z,
+            alpha * dd2 + (1.0 - alpha) * dz,
+            vec![alpha * z * d1 / d2 + (1.0 - alpha) * d1, d2 - z],
+        )
+    }
+}
+
+impl ProjectionJacobian for DoubleSphere {
+    fn parameters(&self) -> Vec<f64> {
+        vec![self.fx, self.fy, self.cx, self.cy, self.xi, self.alpha]
+    }
+    fn set_parameters(&mut self, parameters: &[f64]) {
+        [self.fx, self.fy, self.cx, self.cy, self.xi, self.alpha] = take(parameters);
+    }
+    fn ray_jacobian(&self, ray: &CameraRay) -> Matrix2x3<f64> {
+        central_ray_jacobian(self.fx, self.fy, ray, &self.denominator(ray))
+    }
+    fn parameter_jacobian(&self, ray: &CameraRay) -> DMatrix<f64> {
+        central_parameter_jacobian(self.fx, self.fy, ray, &self.denominator(ray))
+    }
+}
+
+impl Scaramuzza {
+    /// the point `rho(theta) (cos phi, sin phi)` in the OCamCalib frame, its derivative with respect to the
+    /// ray, the elevation angle and the direction
+    fn ocam_point(&self, ray: &CameraRay) -> (Vector2<f64>, Matrix2x3<f64>, f64, Vector2<f64>) {
+        let v = ray.vector;
+        // the OCamCalib frame swaps x and y and flips z
+        let (p, q) = (v.y, v.x);
+        let r = p.hypot(q);
+        let n2 = v.norm_squared();
+        let theta = (-v.z).atan2(r);
+        let rho = Self::evaluate(&self.inverse_polynomial, theta);
+        let drho = self
+            .inverse_polynomial
+            .iter()
+            .enumerate()
+            .skip(1)
+            .rev()
+            .fold(0.0, |acc, (i, c)| acc * theta + i as f64 * c);
+        if r < f64::EPSILON {
+            let jacobian = Matrix2x3::new(0.0, drho / v.z, 0.0, drho / v.z, 0.0, 0.0);
+            return (Vector2::zeros(), jacobian, theta, Vector2::zeros());
+        }
+        let direction = Vector2::new(p / r, q / r);
+        // derivatives with respect to (p, q, z)
+        let dtheta = Vector3::new(v.z * p / (r * n2), v.z * q / (r * n2), -r / n2);
+        let r3 = r * r * r;
+        let ddirection = Matrix2x3::new(q * q / r3, -p * q / r3, 0.0, -p * q / r3, p * p / r3, 0.0);
+        let jacobian = direction * dtheta.transpose() * drho + ddirection * rho;
+        // back to the order of the ray (x = q, y = p, z)
+        let jacobian = Matrix2x3::from_columns(&[
+            jacobian.column(1).into_owned(),
+            jacobian.column(0).into_owned(),
+            jacobian.column(2).into_owned(),
+        ]);
+        (direction * rho, jacobian, theta, direction)
+    }
+}
+
+impl ProjectionJacobian for Scaramuzza {
+    /// the polynomial, the inverse polynomial, `cx`, `cy`, `c`, `d` and `e`
+    fn parameters(&self) -> Vec<f64> {
+        let mut parameters = self.polynomial.clone();
+        parameters.extend(&self.inverse_polynomial);
+        parameters.extend([self.cx, self.cy, self.c, self.d, self.e]);
+        parameters
+    }
+    fn set_parameters(&mut self, parameters: &[f64]) {
+        let (polynomial, rest) = parameters.split_at(self.polynomial.len());
+        let (inverse_polynomial, rest) = rest.split_at(self.inverse_polynomial.len());
+        self.polynomial = polynomial.to_vec();
+        self.inverse_polynomial = inverse_polynomial.to_vec();
+        [self.cx, self.cy, self.c, self.d, self.e] = take(rest);
+    }
+    fn ray_jacobian(&self, ray: &CameraRay) -> Matrix2x3<f64> {
+        let (_, jacobian, _, _) = self.ocam_point(ray);
+        Matrix2::new(self.e, 1.0, self.c, self.d) * jacobian
+    }
+    fn parameter_jacobian(&self, ray: &CameraRay) -> DMatrix<f64> {
+        let (point, _, theta, direction) = self.ocam_point(ray);
+        let (np, ni) = (self.polynomial.len(), self.inverse_polynomial.len());
+        let mut jacobian = DMatrix::zeros(2, np + ni + 5);
+        let mut power = 1.0;
+        for i in 0..ni {
+            jacobian[(0, np + i)] = (self.e * direction.x + direction.y) * power;
+            jacobian[(1, np + i)] = (self.c * direction.x + self.d * direction.y) * power;
+            power *= theta;
+        }
+        let offset = np + ni;
+        jacobian[(0, offset)] = 1.0;
+        jacobian[(1, offset + 1)] = 1.0;
+        jacobian[(1, offset + 2)] = point.x;
+        jacobian[(1, offset + 3)] = point.y;
+        jacobian[(0, offset + 4)] = point.x;
+        jacobian
+    }
+}
+
+impl BSplineCamera {
+    /// the pseudo inverse of the derivative of the imaged direction at the projected pixel and the derivative
+    /// of the normalization, projecting a ray solves `direction(pixel) = ray / |ray|`
+    fn implicit(&self, ray: &CameraRay) -> (Matrix2x3<f64>, Matrix3<f64>, Vec<(usize, f64)>) {
+        let pixel = self.project(ray);
+        let (value, du, dv) = self.evaluate(&pixel);
+        let norm = value.norm();
+        let direction = value / norm;
+        let normalization = (Matrix3::identity() - direction * direction.transpose()) / norm;
+        let jacobian = Matrix3x2::from_columns(&[normalization * du, normalization * dv]);
+        let inverse = (jacobian.transpose() * jacobian)
+            .try_inverse()
+            .map(|inverse| inverse * jacobian.transpose())
+            .unwrap_or_else(Matrix2x3::zeros);
+        let weights = self
+            .weights(&pixel)
+            .into_iter()
+            .map(|(index, weight, _, _)| (index, weight))
+            .collect();
+        (inverse, normalization, weights)
+    }
+}
+
+impl ProjectionJacobian for BSplineCamera {
+    /// the control points as `x, y, z` of `grid[0]`, `grid[1]`, ...
+    fn parameters(&self) -> Vec<f64> {
+        self.grid.iter().flatten().copied().collect()
+    }
+    fn set_parameters(&mut self, parameters: &[f64]) {
+        assert_eq!(
+            parameters.len(),
+            3 * self.grid.len(),
+            "wrong number of parameters"
+        );
+        for (point, parameters) in self.grid.iter_mut().zip(parameters.chunks(3)) {
+            *point = take(parameters);
+        }
+    }
+    fn ray_jacobian(&self, ray: &CameraRay) -> Matrix2x3<f64> {
+        let (inverse, _, _) = self.implicit(ray);
+        let target = ray.vector.normalize();
+        let normalization = (Matrix3::identity() - target * target.transpose()) / ray.vector.norm();
+        inverse * normalization
+    }
+    fn parameter_jacobian(&self, ray: &CameraRay) -> DMatrix<f64> {
+        let (inverse, normalization, weights) = self.implicit(ray);
+        let step = -inverse * normalization;
+        let mut jacobian = DMatrix::zeros(2, 3 * self.grid.len());
+        for (index, weight) in weights {
+            let mut columns = jacobian.columns_mut(3 * index, 3);
+            columns += step * weight;
+        }
+        jacobian
+    }
+}
+
+impl ProjectionJacobian for AnyProjection {
+    fn parameters(&self) -> Vec<f64> {
+        match self {
+            AnyProjection::Pinhole(projection) => projection.parameters(),
+            AnyProjection::Fisheye(projection) => projection.parameters(),
+            AnyProjection::Stereographic(projection) => projection.parameters(),
+            AnyProjection::Equisolid(projection) => projection.parameters(),
+            AnyProjection::Orthographic(projection) => projection.parameters(),
+            AnyProjection::UnifiedCamera(projection) => projection.parameters(),
+            AnyProjection::ExtendedUnifiedCamera(projection) => projection.parameters(),
+            AnyProjection::DoubleSphere(projection) => projection.parameters(),
+            AnyProjection::Scaramuzza(projection) => projection.parameters(),
+            AnyProjection::BSplineCamera(projection) => projection.parameters(),
+        }
+    }
+    fn set_parameters(&mut self, parameters: &[f64]) {
+        match self {
+            AnyProjection::Pinhole(projection) => projection.set_parameters(parameters),
+            AnyProjection::Fisheye(projection) => projection.set_parameters(parameters),
+            AnyProjection::Stereographic(projection) => projection.set_parameters(parameters),
+            AnyProjection::Equisolid(projection) => projection.set_parameters(parameters),
+            AnyProjection::Orthographic(projection) => projection.set_parameters(parameters),
+            AnyProjection::UnifiedCamera(projection) => projection.set_parameters(parameters),
+            AnyProjection::ExtendedUnifiedCamera(projection) => {
+                projection.set_parameters(parameters)
+            }
+            AnyProjection::DoubleSphere(projection) => projection.set_parameters(parameters),
+            AnyProjection::Scaramuzza(projection) => projection.set_parameters(parameters),
+            AnyProjection::BSplineCamera(projection) => projection.set_parameters(parameters),
+        }
+    }
+    fn ray_jacobian(&self, ray: &CameraRay) -> Matrix2x3<f64> {
+        match self {
+            AnyProjection::Pinhole(projection) => projection.ray_jacobian(ray),
+            AnyProjection::Fisheye(projection) => projection.ray_jacobian(ray),
+            AnyProjection::Stereographic(projection) => projection.ray_jacobian(ray),
+            AnyProjection::Equisolid(projection) => projection.ray_jacobian(ray),
+            AnyProjection::Orthographic(projection) => projection.ray_jacobian(ray),
+            AnyProjection::UnifiedCamera(projection) => projection.ray_jacobian(ray),
+            AnyProjection::ExtendedUnifiedCamera(projection) => projection.ray_jacobian(ray),
+            AnyProjection::DoubleSphere(projection) => projection.ray_jacobian(ray),
+            AnyProjection::Scaramuzza(projection) => projection.ray_jacobian(ray),
+            AnyProjection::BSplineCamera(projection) => projection.ray_jacobian(ray),
+        }
+    }
+    fn parameter_jacobian(&self, ray: &CameraRay) -> DMatrix<f64> {
+        match self {
+            AnyProjection::Pinhole(projection) => projection.parameter_jacobian(ray),
+            AnyProjection::Fisheye(projection) => projection.parameter_jacobian(ray),
+            AnyProjection::Stereographic(projection) => projection.parameter_jacobian(ray),
+            AnyProjection::Equisolid(projection) => projection.parameter_jacobian(ray),
+            AnyProjection::Orthographic(projection) => projection.parameter_jacobian(ray),
+            AnyProjection::UnifiedCamera(projection) => projection.parameter_jacobian(ray),
+            AnyProjection::ExtendedUnifiedCamera(projection) => projection.parameter_jacobian(ray),
+            AnyProjection::DoubleSphere(projection) => projection.parameter_jacobian(ray),
+            AnyProjection::Scaramuzza(projection) => projection.parameter_jacobian(ray),
+            AnyProjection::BSplineCamera(projection) => projection.parameter_jacobian(ray),
+        }
+    }
+}
+
+/// the distortions on the normalized image plane only depend on `x` and `y` and have `z = 1`
+fn planar(jacobian: Matrix2<f64>) -> Matrix3<f64> {
+    let mut planar = Matrix3::zeros();
+    planar.fixed_view_mut::<2, 2>(0, 0).copy_from(&jacobian);
+    planar
+}
+
+/// the derivative of a planar distortion of the point `(x / z, y / z)` on the image plane, for the
+/// derivative `jacobian` with respect to that point
+fn image_plane(jacobian: Matrix2<f64>, ray: &CameraRay) -> Matrix3<f64> {
+    let z = ray.vector.z;
+    let (x, y) = ray.plane_xy();
+    let division = Matrix2x3::new(1.0 / z, 0.0, -x / z, 0.0, 1.0 / z, -y / z);
+    let mut planar = Matrix3::zeros();
+    planar
+        .fixed_view_mut::<2, 3>(0, 0)
+        .copy_from(&(jacobian * division));
+    planar
+}
+
+fn planar_parameters(jacobian: DMatrix<f64>) -> DMatrix<f64> {
+    jacobian.insert_row(2, 0.0)
+}
+
+/// the derivative of `s(r) (x, y)` for the scale `s` and its derivative `ds/dr`
+fn radial_scale(x: f64, y: f64, s: f64, ds: f64) -> Matrix2<f64> {
+    let r = x.hypot(y);
+    let point = Vector2::new(x, y);
+    Matrix2::identity() * s + point * point.transpose() * (ds / r)
+}
+
+/// the derivatives of the thin prism model with respect to `x, y` and to all of its 14 coefficients,
+/// `PlumbBob` and `Rational` are special cases of it
+fn thin_prism_jacobians(distortion: &ThinPrism, x: f64, y: f64) -> (Matrix2<f64>, DMatrix<f64>) {
+    let ThinPrism {
+        k1,
+        k2,
+        p1,
+        p2,
+        k3,
+        k4,
+        k5,
+        k6,
+        s1,
+        s2,
+        s3,
+        s4,
+        ..
+    } = *distortion;
+    let (x2, y2, xy, r2, r4, r6) = PlumbBob::params(x, y);
+    let numerator = 1.0 + k1 * r2 + k2 * r4 + k3 * r6;
+    let denominator = 1.0 + k4 * r2 + k5 * r4 + k6 * r6;
+    let radial = numerator / denominator;
+    // derivatives with respect to r2
+    let dnumerator = k1 + 2.0 * k2 * r2 + 3.0 * k3 * r4;
+    let ddenominator = k4 + 2.0 * k5 * r2 + 3.0 * k6 * r4;
+    let dradial =
+        (dnumerator * denominator - numerator * ddenominator) / (denominator * denominator);
+    let dprism_x = s1 + 2.0 * s2 * r2;
+    let dprism_y = s3 + 2.0 * s4 * r2;
+    let point = Matrix2::new(
+        radial + 2.0 * x2 * dradial + 2.0 * p1 * y + 6.0 * p2 * x + 2.0 * x * dprism_x,
+        2.0 * xy * dradial + 2.0 * p1 * x + 2.0 * p2 * y + 2.0 * y * dprism_x,
+        2.0 * xy * dradial + 2.0 * p1 * x + 2.0 * p2 * y + 2.0 * x * dprism_y,
+        radial + 2.0 * y2 * dradial + 6.0 * p1 * y + 2.0 * p2 * x + 2.0 * y * dprism_y,
+    );
+    let dk = |power: f64| (x * power / denominator, y * power / denominator);
+    let dk_denominator = |power: f64| {
+        let scale = -numerator * power / (denominator * denominator);
+        (x * scale, y * scale)
+    };
+    let columns = [
+        dk(r2),
+        dk(r4),
+        (2.0 * xy, r2 + 2.0 * y2),
+        (r2 + 2.0 * x2, 2.0 * xy),
+        dk(r6),
+        dk_denominator(r2),
+        dk_denominator(r4),
+        dk_denominator(r6),
+        (r2, 0.0),
+        (r4, 0.0),
+        (0.0, r2),
+        (0.0, r4),
+    ];
+    let (xd, yd) = (
+        radial * x + 2.0 * p1 * xy + p2 * (r2 + 2.0 * x2) + s1 * r2 + s2 * r4,
+        radial * y + p1 * (r2 + 2.0 * y2) + 2.0 * p2 * xy + s3 * r2 + s4 * r4,
+    );
+    let tilt = distortion.tilt();
+    let undistorted = Vector3::new(xd, yd, 1.0);
+    let tilted = tilt * undistorted;
+    let division = if tilted.z != 0.0 {
+        Matrix2x3::new(
+            1.0 / tilted.z,
+            0.0,
+            -tilted.x / (tilted.z * tilted.z),
+            0.0,
+            1.0 / tilted.z,
+            -tilted.y / (tilted.z * tilted.z),
+        )
+    } else {
+        Matrix2x3::new(1.0, 0.0, 0.0, 0.0, 1.0, 0.0)
+    };
+    let outer = division * tilt.fixed_view::<3, 2>(0, 0);
+    let mut parameters = DMatrix::zeros(2, 14);
+    for (i, (dx, dy)) in columns.into_iter().enumerate() {
+        parameters.set_column(i, &(outer * Vector2::new(dx, dy)));
+    }
+    let (dtilt_x, dtilt_y) = tilt_derivatives(distortion.tau_x, distortion.tau_y);
+    parameters.set_column(12, &(division * dtilt_x * undistorted));
+    parameters.set_column(13, &(division * dtilt_y * undistorted));
+    (outer * point, parameters)
+}
+
+/// the derivatives of `ThinPrism::tilt` with respect to `tau_x` and `tau_y`
+fn tilt_derivatives(tau_x: f64, tau_y: f64) -> (Matrix3<f64>, Matrix3<f64>) {
+    let (sin_x, cos_x) = tau_x.sin_cos();
+    let (sin_y, cos_y) = tau_y.sin_cos();
+    let rot_x = Matrix3::new(1.0, 0.0, 0.0, 0.0, cos_x, sin_x, 0.0, -sin_x, cos_x);
+    let rot_y = Matrix3::new(cos_y, 0.0, -sin_y, 0.0, 1.0, 0.0, sin_y, 0.0, cos_y);
+    let drot_x = Matrix3::new(0.0, 0.0, 0.0, 0.0, -sin_x, cos_x, 0.0, -cos_x, -sin_x);
+    let drot_y = Matrix3::new(-sin_y, 0.0, -cos_y, 0.0, 0.0, 0.0, cos_y, 0.0, -sin_y);
+    let proj_z = |rot: &Matrix3<f64>, homogeneous: f64| {
+        Matrix3::new(
+            rot[(2, 2)],
+            0.0,
+            -rot[(0, 2)],
+            0.0,
+            rot[(2, 2)],
+            -rot[(1, 2)],
+            0.0,
+            0.0,
+            homogeneous,
+        )
+    };
+    let rot = rot_y * rot_x;
+    let derivative = |drot: Matrix3<f64>| proj_z(&drot, 0.0) * rot + proj_z(&rot, 1.0) * drot;
+    (derivative(rot_y * drot_x), derivative(drot_y * rot_x))
+}
+
+impl DistortionJacobian for ThinPrism {
+    fn parameters(&self) -> Vec<f64> {
+        vec![
+            self.k1, self.k2, self.p1, self.p2, self.k3, self.k4, self.k5, self.k6, self.s1,
+            self.s2, self.s3, self.s4, self.tau_x, self.tau_y,
+        ]
+    }
+    fn set_parameters(&mut self, parameters: &[f64]) {
+        [
+            self.k1, self.k2, self.p1, self.p2, self.k3, self.k4, self.k5, self.k6, self.s1,
+            self.s2, self.s3, self.s4, self.tau_x, self.tau_y,
+        ] = take(parameters);
+    }
+    fn ray_jacobian(&self, ray: &CameraRay) -> Matrix3<f64> {
+        let (x, y) = ray.plane_xy();
+        image_plane(thin_prism_jacobians(self, x, y).0, ray)
+    }
+    fn parameter_jacobian(&self, ray: &CameraRay) -> DMatrix<f64> {
+        let (x, y) = ray.plane_xy();
+        planar_parameters(thin_prism_jacobians(self, x, y).1)
+    }
+}
+
+impl DistortionJacobian for Rational {
+    fn parameters(&self) -> Vec<f64> {
+        vec![
+            self.k1, self.k2, self.p1, self.p2, self.k3, self.k4, self.k5, self.k6,
+        ]
+    }
+    fn set_parameters(&mut self, parameters: &[f64]) {
+        [
+            self.k1, self.k2, self.p1, self.p2, self.k3, self.k4, self.k5, self.k6,
+        ] = take(parameters);
+    }
+    fn ray_jacobian(&self, ray: &CameraRay) -> Matrix3<f64> {
+        ThinPrism::from(*self).ray_jacobian(ray)
+    }
+    fn parameter_jacobian(&self, ray: &CameraRay) -> DMatrix<f64> {
+        ThinPrism::from(*self)
+            .parameter_jacobian(ray)
+            .columns(0, 8)
+            .into_owned()
+    }
+}
+
+impl DistortionJacobian for PlumbBob {
+    fn parameters(&self) -> Vec<f64> {
+        vec![self.k1, self.k2, self.p1, self.p2, self.k3]
+    }
+    fn set_parameters(&mut self, parameters: &[f64]) {
+        [self.k1, self.k2, self.p1, self.p2, self.k3] = take(parameters);
+    }
+    fn ray_jacobian(&self, ray: &CameraRay) -> Matrix3<f64> {
+        Rational::from(*self).ray_jacobian(ray)
+    }
+    fn parameter_jacobian(&self, ray: &CameraRay) -> DMatrix<f64> {
+        Rational::from(*self)
+            .parameter_jacobian(ray)
+            .columns(0, 5)
+            .into_owned()
+    }
+}
+
+impl Fisheye {
+    /// unlike the other planar distortions the angle `atan2(r, z)` depends on `z` too
+    fn jacobians(&self, ray: &CameraRay) -> (Matrix3<f64>, DMatrix<f64>) {
+        let (x, y, z) = (ray.vector.x, ray.vector.y, ray.vector.z);
+        let (r, theta, theta2, theta4, theta6, theta8, x, y) = Self::params(x, y, z);
+        if r < f64::EPSILON {
+            let mut jacobian = planar(Matrix2::identity() / z);
+            jacobian[(0, 2)] = -x / (z * z);
+            jacobian[(1, 2)] = -y / (z * z);
+            return (jacobian, DMatrix::zeros(2, 4));
+        }
+        let theta_d = theta
+            * (1.0 + self.k1 * theta2 + self.k2 * theta4 + self.k3 * theta6 + self.k4 * theta8);
+        let dtheta_d = 1.0
+            + 3.0 * self.k1 * theta2
+            + 5.0 * self.k2 * theta4
+            + 7.0 * self.k3 * theta6
+            + 9.0 * self.k4 * theta8;
+        let n2 = r * r + z * z;
+        let ds = (dtheta_d * z / n2 * r - theta_d) / (r * r);
+        let mut jacobian = planar(radial_scale(x, y, theta_d / r, ds));
+        let ds_dz = -dtheta_d / n2;
+        jacobian[(0, 2)] = x * ds_dz;
+        jacobian[(1, 2)] = y * ds_dz;
+        let mut parameters = DMatrix::zeros(2, 4);
+        for (i, power) in [theta2, theta4, theta6, theta8].into_iter().enumerate() {
+            parameters[(0, i)] = x * theta * power / r;
+            parameters[(1, i)] = y * theta * power / r;
+        }
+        (jacobian, parameters)
+    }
+}
+
+impl DistortionJacobian for Fisheye {
+    /// `k1` to `k4`, the skew `s` is not used by the distortion
+    fn parameters(&self) -> Vec<f64> {
+        vec![self.k1, self.k2, self.k3, self.k4]
+    }
+    fn set_parameters(&mut self, parameters: &[f64]) {
+        [self.k1, self.k2, self.k3, self.k4] = take(parameters);
+    }
+    fn ray_jacobian(&self, ray: &CameraRay) -> Matrix3<f64> {
+        self.jacobians(ray).0
+    }
+    fn parameter_jacobian(&self, ray: &CameraRay) -> DMatrix<f64> {
+        planar_parameters(self.jacobians(ray).1)
+    }
+}
+
+impl FovDistortion {
+    fn jacobians(&self, ray: &CameraRay) -> (Matrix2<f64>, DMatrix<f64>) {
+        let (x, y) = ray.plane_xy();
+        let r = x.hypot(y);
+        let omega = self.omega;
+        if r < f64::EPSILON || omega.abs() < f64::EPSILON {
+            // the scale is even in omega, so its derivative vanishes at omega = 0
+            return (Matrix2::identity(), DMatrix::zeros(2, 1));
+        }
+        let tan = (omega / 2.0).tan();
+        let a = 2.0 * tan;
+        let atan = (a * r).atan();
+        let scale = atan / (omega * r);
+        let ds = (a * r / (1.0 + a * a * r * r) - atan) / (omega * r * r);
+        let da = 1.0 + tan * tan;
+        let domega = da / (omega * (1.0 + a * a * r * r)) - atan / (omega * omega * r);
+        (
+            radial_scale(x, y, scale, ds),
+            DMatrix::from_column_slice(2, 1, &[x * domega, y * domega]),
+        )
+    }
+}
+
+impl DistortionJacobian for FovDistortion {
+    fn parameters(&self) -> Vec<f64> {
+        vec![self.omega]
+    }
+    fn set_parameters(&mut self, parameters: &[f64]) {
+        [self.omega] = take(parameters);
+    }
+    fn ray_jacobian(&self, ray: &CameraRay) -> Matrix3<f64> {
+        image_plane(self.jacobians(ray).0, ray)
+    }
+    fn parameter_jacobian(&self, ray: &CameraRay) -> DMatrix<f64> {
+        planar_parameters(self.jacobians(ray).1)
+    }
+}
+
+impl Division {
+    fn jacobians(&self, ray: &CameraRay) -> (Matrix2<f64>, DMatrix<f64>) {
+        let (x, y) = ray.plane_xy();
+        let r_u = x.hypot(y);
+        if r_u < f64::EPSILON {
+            return (Matrix2::identity(), DMatrix::zeros(2, 2));
+        }
+        // implicit derivatives of r_d - r_u (1 + lambda1 r_d^2 + lambda2 r_d^4) = 0
+        let r_d = self.distorted_radius(r_u).unwrap_or(f64::NAN);
+        let r2 = r_d * r_d;
+        let factor = 1.0 + self.lambda1 * r2 + self.lambda2 * r2 * r2;
+        let denominator = 1.0 - r_u * (2.0 * self.lambda1 * r_d + 4.0 * self.lambda2 * r2 * r_d);
+        let dr_d = factor / denominator;
+        let ds = (dr_d * r_u - r_d) / (r_u * r_u);
+        let (dlambda1, dlambda2) = (r2 / denominator, r2 * r2 / denominator);
+        (
+            radial_scale(x, y, r_d / r_u, ds),
+            DMatrix::from_column_slice(
+                2,
+                2,
+                &[x * dlambda1, y * dlambda1, x * dlambda2, y * dlambda2],
+            ),
+        )
+    }
+}
+
+impl DistortionJacobian for Division {
+    fn parameters(&self) -> Vec<f64> {
+        vec![self.lambda1, self.lambda2]
+    }
+    fn set_parameters(&mut self, parameters: &[f64]) {
+        [self.lambda1, self.lambda2] = take(parameters);
+    }
+    fn ray_jacobian(&self, ray: &CameraRay) -> Matrix3<f64> {
+        image_plane(self.jacobians(ray).0, ray)
+    }
+    fn parameter_jacobian(&self, ray: &CameraRay) -> DMatrix<f64> {
+        planar_parameters(self.jacobians(ray).1)
+    }
+}
+
+impl DistortionJacobian for Ideal {
+    fn parameters(&self) -> Vec<f64> {
+        Vec::new()
+    }
+    fn set_parameters(&mut self, parameters: &[f64]) {
+        [] = take(parameters);
+    }
+    fn ray_jacobian(&self, _ray: &CameraRay) -> Matrix3<f64> {
+        Matrix3::identity()
+    }
+    fn parameter_jacobian(&self, _ray: &CameraRay) -> DMatrix<f64> {
+        DMatrix::zeros(3, 0)
+    }
+}
+
+impl DistortionJacobian for PinholeDistortion {
+    fn parameters(&self) -> Vec<f64> {
+        match self {
+            PinholeDistortion::ThinPrism(distortion) => distortion.parameters(),
+            PinholeDistortion::Rational(distortion) => distortion.parameters(),
+            PinholeDistortion::PlumbBob(distortion) => distortion.parameters(),
+            PinholeDistortion::Fov(distortion) => distortion.parameters(),
+            PinholeDistortion::Division(distortion) => distortion.parameters(),
+        }
+    }
+    fn set_parameters(&mut self, parameters: &[f64]) {
+        match self {
+            PinholeDistortion::ThinPrism(distortion) => distortion.set_parameters(parameters),
+            PinholeDistortion::Rational(distortion) => distortion.set_parameters(parameters),
+            PinholeDistortion::PlumbBob(distortion) => distortion.set_parameters(parameters),
+            PinholeDistortion::Fov(distortion) => distortion.set_parameters(parameters),
+            PinholeDistortion::Division(distortion) => distortion.set_parameters(parameters),
+        }
+    }
+    fn ray_jacobian(&self, ray: &CameraRay) -> Matrix3<f64> {
+        match self {
+            PinholeDistortion::ThinPrism(distortion) => distortion.ray_jacobian(ray),
+            PinholeDistortion::Rational(distortion) => distortion.ray_jacobian(ray),
+            PinholeDistortion::PlumbBob(distortion) => distortion.ray_jacobian(ray),
+            PinholeDistortion::Fov(distortion) => distortion.ray_jacobian(ray),
+            PinholeDistortion::Division(distortion) => distortion.ray_jacobian(ray),
+        }
+    }
+    fn parameter_jacobian(&self, ray: &CameraRay) -> DMatrix<f64> {
+        match self {
+            PinholeDistortion::ThinPrism(distortion) => distortion.parameter_jacobian(ray),
+            PinholeDistortion::Rational(distortion) => distortion.parameter_jacobian(ray),
+            PinholeDistortion::PlumbBob(distortion) => distortion.parameter_jacobian(ray),
+            PinholeDistortion::Fov(distortion) => distortion.parameter_jacobian(ray),
+            PinholeDistortion::Division(distortion) => distortion.parameter_jacobian(ray),
+        }
+    }
+}
+
+impl DistortionJacobian for AnyDistortion {
+    fn parameters(&self) -> Vec<f64> {
+        match self {
+            AnyDistortion::Ideal => Ideal {}.parameters(),
+            AnyDistortion::PlumbBob(distortion) => distortion.parameters(),
+            AnyDistortion::Rational(distortion) => distortion.parameters(),
+            AnyDistortion::ThinPrism(distortion) => distortion.parameters(),
+            AnyDistortion::Fisheye(distortion) => distortion.parameters(),
+            AnyDistortion::Fov(distortion) => distortion.parameters(),
+            AnyDistortion::Division(distortion) => distortion.parameters(),
+        }
+    }
+    fn set_parameters(&mut self, parameters: &[f64]) {
+        match self {
+            AnyDistortion::Ideal => Ideal {}.set_parameters(parameters),
+            AnyDistortion::PlumbBob(distortion) => distortion.set_parameters(parameters),
+            AnyDistortion::Rational(distortion) => distortion.set_parameters(parameters),
+            AnyDistortion::ThinPrism(distortion) => distortion.set_parameters(parameters),
+            AnyDistortion::Fisheye(distortion) => distortion.set_parameters(parameters),
+            AnyDistortion::Fov(distortion) => distortion.set_parameters(parameters),
+            AnyDistortion::Division(distortion) => distortion.set_parameters(parameters),
+        }
+    }
+    fn ray_jacobian(&self, ray: &CameraRay) -> Matrix3<f64> {
+        match self {
+            AnyDistortion::Ideal => Ideal {}.ray_jacobian(ray),
+            AnyDistortion::PlumbBob(distortion) => distortion.ray_jacobian(ray),
+            AnyDistortion::Rational(distortion) => distortion.ray_jacobian(ray),
+            AnyDistortion::ThinPrism(distortion) => distortion.ray_jacobian(ray),
+            AnyDistortion::Fisheye(distortion) => distortion.ray_jacobian(ray),
+            AnyDistortion::Fov(distortion) => distortion.ray_jacobian(ray),
+            AnyDistortion::Division(distortion) => distortion.ray_jacobian(ray),
+        }
+    }
+    fn parameter_jacobian(&self, ray: &CameraRay) -> DMatrix<f64> {
+        match self {
+            AnyDistortion::Ideal => Ideal {}.parameter_jacobian(ray),
+            AnyDistortion::PlumbBob(distortion) => distortion.parameter_jacobian(ray),
+            AnyDistortion::Rational(distortion) => distortion.parameter_jacobian(ray),
+            AnyDistortion::ThinPrism(distortion) => distortion.parameter_jacobian(ray),
+            AnyDistortion::Fisheye(distortion) => distortion.parameter_jacobian(ray),
+            AnyDistortion::Fov(distortion) => distortion.parameter_jacobian(ray),
+            AnyDistortion::Division(distortion) => distortion.parameter_jacobian(ray),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::PixelIndex;
+
+    const RAYS: [(f64, f64, f64); 4] = [
+        (0.2, -0.1, 1.0),
+        (-0.5, 0.4, 0.8),
+        (0.05, 0.02, 1.0),
+        (0.6, 0.5, 1.2),
+    ];
+    const STEP: f64 = 1e-6;
+
+    fn assert_close(analytic: &DMatrix<f64>, numeric: &DMatrix<f64>, tolerance: f64) {
+        assert_eq!(analytic.shape(), numeric.shape());
+        for (a, n) in analytic.iter().zip(numeric.iter()) {
+            assert!(
+                (a - n).abs() <= tolerance * n.abs().max(1.0),
+                "analytic\n{}numeric\n{}",
+                analytic,
+                numeric
+            );
+        }
+    }
+
+    fn pixel_difference(plus: PixelIndex<f64>, minus: PixelIndex<f64>, step: f64) -> [f64; 2] {
+        [(plus.0 - minus.0) / step, (plus.1 - minus.1) / step]
+    }
+
+    /// central differences of `f` for every parameter
+    fn numeric<M: Clone>(
+        model: &M,
+        parameters: Vec<f64>,
+        set: impl Fn(&mut M, &[f64]),
+        rows: usize,
+        f: impl Fn(&M) -> Vec<f64>,
+    ) -> DMatrix<f64> {
+        let mut jacobian = DMatrix::zeros(rows, parameters.len());
+        for i in 0..parameters.len() {
+            let step = STEP * parameters[i].abs().max(1.0);
+            let evaluate = |offset: f64| {
+                let mut parameters = parameters.clone();
+                parameters[i] += offset;
+                let mut model = model.clone();
+                set(&mut model, &parameters);
+                f(&model)
+            };
+            let (plus, minus) = (evaluate(step), evaluate(-step));
+            for row in 0..rows {
+                jacobian[(row, i)] = (plus[row] - minus[row]) / (2.0 * step);
+            }
+        }
+        jacobian
+    }
+
+    fn to_dmatrix<const R: usize, const C: usize>(
+        matrix: &nalgebra::SMatrix<f64, R, C>,
+    ) -> DMatrix<f64> {
+        DMatrix::from_iterator(R, C, matrix.iter().copied())
+    }
+
+    fn assert_projection<P: ProjectionJacobian + Clone>(projection: &P, tolerance: f64) {
+        assert_projection_at(projection, &RAYS, tolerance);
+    }
+
+    fn assert_projection_at<P: ProjectionJacobian + Clone>(
+        projection: &P,
+        rays: &[(f64, f64, f64)],
+        tolerance: f64,
+    ) {
+        for &(x, y, z) in rays {
+            let ray = CameraRay::new(x, y, z);
+            let numeric_ray = DMatrix::from_fn(2, 3, |row, col| {
+                let mut plus = ray;
+                let mut minus = ray;
+                plus.vector[col] += STEP;
+                minus.vector[col] -= STEP;
+                pixel_difference(
+                    projection.project(&plus),
+                    projection.project(&minus),
+                    2.0 * STEP,
+                )[row]
+            });
+            assert_close(
+                &to_dmatrix(&projection.ray_jacobian(&ray)),
+                &numeric_ray,
+                tolerance,
+            );
+            let numeric_parameters = numeric(
+                projection,
+                projection.parameters(),
+                |p, parameters| p.set_parameters(parameters),
+                2,
+                |p| {
+                    let PixelIndex(u, v) = p.project(&ray);
+                    vec![u, v]
+                },
+            );
+            assert_close(
+                &projection.parameter_jacobian(&ray),
+                &numeric_parameters,
+                tolerance,
+            );
+        }
+    }
+
+    fn assert_distortion<D: DistortionJacobian + Clone>(distortion: &D) {
+        assert_distortion_at(distortion, &RAYS);
+    }
+
+    fn assert_distortion_at<D: DistortionJacobian + Clone>(
+        distortion: &D,
+        rays: &[(f64, f64, f64)],
+    ) {
+        for &(x, y, z) in rays {
+            let ray = CameraRay::new(x, y, z);
+            let numeric_ray = DMatrix::from_fn(3, 3, |row, col| {
+                let mut plus = ray;
+                let mut minus = ray;
+                plus.vector[col] += STEP;
+                minus.vector[col] -= STEP;
+                (distortion.distort(&plus).vector[row] - distortion.distort(&minus).vector[row])
+                    / (2.0 * STEP)
+            });
+            assert_close(
+                &to_dmatrix(&distortion.ray_jacobian(&ray)),
+                &numeric_ray,
+                1e-6,
+            );
+            let numeric_parameters = numeric(
+                distortion,
+                distortion.parameters(),
+                |d, parameters| d.set_parameters(parameters),
+                3,
+                |d| d.distort(&ray).vector.iter().copied().collect(),
+            );
+            assert_close(
+                &distortion.parameter_jacobian(&ray),
+                &numeric_parameters,
+                1e-6,
+            );
+        }
+    }
+
+    #[test]
+    fn test_radial_projections() {
+        assert_projection(&Pinhole::new(400.0, 410.0, 320.0, 240.0, 0.5), 1e-6);
+        let fisheye = projection::Fisheye {
+            fx: 300.0,
+            fy: 310.0,
+            cx: 320.0,
+            cy: 240.0,
+            skew: 0.3,
+        };
+        assert_projection(&fisheye, 1e-6);
+        assert_projection_at(&fisheye, &[(0.5, 0.2, 0.0), (-0.3, 0.6, -0.4)], 1e-6);
+        assert_projection(&Stereographic::new(300.0, 310.0, 320.0, 240.0, 0.3), 1e-6);
+        assert_projection(&Equisolid::new(300.0, 310.0, 320.0, 240.0, 0.3), 1e-6);
+        assert_projection(&Orthographic::new(300.0, 310.0, 320.0, 240.0, 0.3), 1e-6);
+    }
+    #[test]
+    fn test_central_projections() {
+        assert_projection(&UnifiedCamera::new(400.0, 410.0, 320.0, 240.0, 0.9), 1e-6);
+        let eucm = ExtendedUnifiedCamera::new(400.0, 410.0, 320.0, 240.0, 0.6, 1.1);
+        assert_projection(&eucm, 1e-6);
+        let double_sphere = DoubleSphere::new(300.0, 310.0, 320.0, 240.0, -0.2, 0.6);
+        assert_projection(&double_sphere, 1e-6);
+    }
+    #[test]
+    fn test_scaramuzza() {
+        let scaramuzza = Scaramuzza {
+            polynomial: vec![-189.8, 0.0, 2.8e-3, -2.5e-6, 1.0e-8],
+            inverse_polynomial: vec![300.0, 160.0, -3.0, 12.0, 2.0],
+            cx: 489.4,
+            cy: 374.2,
+            c: 0.998,
+            d: -0.002,
+            e: 0.0009,
+        };
+        assert_projection(&scaramuzza, 1e-6);
+    }
+    #[test]
+    fn test_bspline() {
+        let stereographic = Stereographic::new(300.0, 310.0, 320.0, 240.0, 0.0);
+        let bspline = BSplineCamera::fit(&stereographic, (0.0, 0.0), (640.0, 480.0), 6, 5).unwrap();
+        // projecting is iterative, which limits the accuracy of the finite differences
+        assert_projection(&bspline, 1e-4);
+    }
+    #[test]
+    fn test_distortions() {
+        assert_distortion(&PlumbBob::new(0.3, -0.1, 0.01, -0.02, 0.05));
+        assert_distortion(&Rational {
+            k1: 0.3,
+            k2: -0.1,
+            p1: 0.01,
+            p2: -0.02,
+            k3: 0.05,
+            k4: 0.1,
+            k5: 0.02,
+            k6: -0.01,
+        });
+        assert_distortion(&ThinPrism {
+            k1: 0.3,
+            k2: -0.1,
+            p1: 0.01,
+            p2: -0.02,
+            k3: 0.05,
+            k4: 0.1,
+            k5: 0.02,
+            k6: -0.01,
+            s1: 0.004,
+            s2: -0.001,
+            s3: 0.002,
+            s4: 0.0005,
+            tau_x: 0.01,
+            tau_y: -0.02,
+        });
+        assert_distortion(&Fisheye {
+            k1: 0.1,
+            k2: -0.02,
+            k3: 0.003,
+            k4: -0.001,
+            s: 0.0,
+        });
+        assert_distortion(&FovDistortion::new(0.9));
+        // the angle of the fisheye depends on z, also for rays behind the image plane
+        let fisheye = Fisheye {
+            k1: 0.02,
+            k2: -0.003,
+            k3: 0.0005,
+            k4: -0.0001,
+            s: 0.0,
+        };
+        assert_distortion_at(&fisheye, &RAYS);
+        assert_distortion_at(&fisheye, &[(0.5, 0.2, 0.0), (-0.3, 0.6, -0.4)]);
+        assert_distortion(&Division::new(-0.2, 0.03));
+        assert_distortion(&AnyDistortion::Ideal);
+    }
+    #[test]
+    fn test_camera_model() {
+        let projection = Pinhole::new(400.0, 410.0, 320.0, 240.0, 0.5);
+        let distortion = PlumbBob::new(0.3, -0.1, 0.01, -0.02, 0.05);
+        let camera = CameraModel::new(projection, distortion);
+        let (np, nd) = (5, 5);
+        for (x, y, _) in RAYS {
+            let ray = CameraRay::new(x, y, 1.0);
+            let numeric_ray = DMatrix::from_fn(2, 3, |row, col| {
+                let mut plus = ray;
+                let mut minus = ray;
+                plus.vector[col] += STEP;
+                minus.vector[col] -= STEP;
+                pixel_difference(camera.project(plus), camera.project(minus), 2.0 * STEP)[row]
+            });
+            assert_close(&to_dmatrix(&camera.ray_jacobian(&ray)), &numeric_ray, 1e-6);
+            let mut parameters = projection.parameters();
+            parameters.extend(distortion.parameters());
+            let numeric_parameters = numeric(
+                &camera,
+                parameters,
+                |camera, parameters| {
+                    let mut projection = *camera.projection();
+                    let mut distortion = *camera.distortion();
+                    projection.set_parameters(&parameters[..np]);
+                    distortion.set_parameters(&parameters[np..np + nd]);
+                    *camera = CameraModel::new(projection, distortion);
+                },
+                2,
+                |camera| {
+                    let PixelIndex(u, v) = camera.project(ray);
+                    vec![u, v]
+                },
+            );
+            assert_close(&camera.parameter_jacobian(&ray), &numeric_parameters, 1e-6);
+        }
+    }
+}
@@ -1,7 +1,21 @@
+pub mod calibration;
 mod camera;
-mod distortion;
-mod projection;
+pub mod distortion;
+mod jacobian;
+pub mod ocam;
+pub mod opencv;
+pub mod projection;
+mod remap;
 
-pub use camera::{Camera, CameraModel, CameraRay, PixelIndex};
-pub use distortion::{CameraDistortion, Fisheye, Ideal, PlumbBob};
-pub use projection::{CameraProjection, Pinhole};
+pub use camera::{Camera, CameraModel, CameraRay, PixelIndex, Ray};
+pub use distortion::{
+    AnyDistortion, CameraDistortion, DistortionError, Division, Fisheye, FovDistortion, Ideal,
+    IterativeSolver, PinholeDistortion, PlumbBob, Rational, ThinPrism, TransformableDistortion,
+};
+pub use jacobian::{DistortionJacobian, ProjectionJacobian};
+pub use projection::{
+    AffineIntrinsics, AnyProjection, BSplineCamera, BSplineError, CameraProjection, DoubleSphere,
+    Equisolid, ExtendedUnifiedCamera, ImageTransform, Orthographic, Pinhole, Scaramuzza,
+    Stereographic, UnifiedCamera,
+};
+pub use remap::{Border, Interpolation, RemapMap};
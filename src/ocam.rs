@@ -0,0 +1,228 @@
+//! reading and writing of the `calib_results.txt` files of the OCamCalib toolbox
+use crate::camera::CameraModel;
+use crate::distortion::Ideal;
+use crate::projection::Scaramuzza;
+
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+#[derive(Debug)]
+pub enum OcamError {
+    Io(std::io::Error),
+    /// the file is not a valid `calib_results.txt`, the string describes the problem
+    Parse(String),
+}
+impl fmt::Display for OcamError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OcamError::Io(err) => write!(f, "io error: {}", err),
+            OcamError::Parse(msg) => write!(f, "parse error: {}", msg),
+        }
+    }
+}
+impl std::error::Error for OcamError {}
+impl From<std::io::Error> for OcamError {
+    fn from(err: std::io::Error) -> Self {
+        OcamError::Io(err)
+    }
+}
+
+/// a calibration as stored by OCamCalib, the resolution is given as `(width, height)`
+#[derive(Debug, Clone, PartialEq)]
+pub struct OcamCalibration {
+    pub model: Scaramuzza,
+    pub resolution: (u32, u32),
+}
+
+impl OcamCalibration {
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, OcamError> {
+        Self::parse(&fs::read_to_string(path)?)
+    }
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), OcamError> {
+        fs::write(path, self.to_calib_results())?;
+        Ok(())
+    }
+
+    /// parses the content of a `calib_results.txt`, comments start with `#`
+    pub fn parse(contents: &str) -> Result<Self, OcamError> {
+        let mut lines = contents
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'));
+        let mut next = |name: &str| -> Result<Vec<f64>, OcamError> {
+            let line = lines
+                .next()
+                .ok_or_else(|| OcamError::Parse(format!("missing {}", name)))?;
+            line.split_whitespace()
+                .map(|value| {
+                    value
+                        .parse::<f64>()
+                        .map_err(|_| OcamError::Parse(format!("invalid {}: '{}'", name, value)))
+                })
+                .collect()
+        };
+        let polynomial = counted(next("polynomial")?, "polynomial")?;
+        let inverse_polynomial = counted(next("inverse polynomial")?, "inverse polynomial")?;
+        let (row, col) = match next("center")?[..] {
+            [row, col] => (row, col),
+            _ => return Err(OcamError::Parse("the center needs two values".to_string())),
+        };
+        let (c, d, e) = match next("affine parameters")?[..] {
+            [c, d, e] => (c, d, e),
+            _ => {
+                return Err(OcamError::Parse(
+                    "expected the affine parameters c, d and e".to_string(),
+                ))
+            }
+        };
+        let (height, width) = match next("image size")?[..] {
+            [height, width] => {
+                let size = |value: f64| {
+                    (value.fract() == 0.0 && (0.0..=u32::MAX as f64).contains(&value))
+                        .then_some(value as u32)
+                        .ok_or_else(|| OcamError::Parse(format!("invalid image size: '{}'", value)))
+                };
+                (size(height)?, size(width)?)
+            }
+            _ => {
+                return Err(OcamError::Parse(
+                    "the image size needs two values".to_string(),
+                ))
+            }
+        };
+        Ok(Self {
+            model: Scaramuzza {
+                polynomial,
+                inverse_polynomial,
+                cx: col,
+                cy: row,
+                c,
+                d,
+                e,
+            },
+            resolution: (width, height),
+        })
+    }
+
+    /// the camera with the resolution of the calibration, the polynomial already contains the distortion
+    pub fn camera_model(&self) -> CameraModel<Scaramuzza, Ideal> {
+        CameraModel::new(self.model.clone(), Ideal {}).with_resolution(self.resolution)
+    }
+
+    pub fn to_calib_results(&self) -> String {
+        let model = &self.model;
+        let counted = |coefficients: &[f64]| {
+            let mut line = coefficients.len().to_string();
+            for c in coefficients {
+                line.push_str(&format!(" {:e}", c));
+            }
+            line
+        };
+        format!(
+            "#polynomial coefficients for the DIRECT mapping function (ocam_model.ss in MATLAB). These are used by cam2world\n\n{}\n\n\
+             #polynomial coefficients for the inverse mapping function (ocam_model.invpol in MATLAB). These are used by world2cam\n\n{}\n\n\
+             #center: \"row\" and \"column\", starting from 0 (C convention)\n\n{:?} {:?}\n\n\
+             #affine parameters \"c\", \"d\", \"e\"\n\n{:?} {:?} {:?}\n\n\
+             #image size: \"height\" and \"width\"\n\n{} {}\n",
+            counted(&model.polynomial),
+            counted(&model.inverse_polynomial),
+            model.cy,
+            model.cx,
+            model.c,
+            model.d,
+            model.e,
+            self.resolution.1,
+            self.resolution.0,
+        )
+    }
+}
+
+/// the coefficient lines start with the number of coefficients
+fn counted(values: Vec<f64>, name: &str) -> Result<Vec<f64>, OcamError> {
+    match values.split_first() {
+        Some((count, coefficients)) if *count as usize == coefficients.len() => {
+            Ok(coefficients.to_vec())
+        }
+        _ => Err(OcamError::Parse(format!(
+            "the {} does not have the announced number of coefficients",
+            name
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CameraProjection, Pinhole, PixelIndex, RemapMap};
+
+    const CALIB_RESULTS: &str = r#"#polynomial coefficients for the DIRECT mapping function (ocam_model.ss in MATLAB). These are used by cam2world
+
+5 -1.898232e+02 0.000000e+00 2.776063e-03 -2.511565e-06 1.008096e-08 
+
+#polynomial coefficients for the inverse mapping function (ocam_model.invpol in MATLAB). These are used by world2cam
+
+4 270.5 150.25 -3.5 12.0 
+
+#center: "row" and "column", starting from 0 (C convention)
+
+374.175359 489.391537
+
+#affine parameters "c", "d", "e"
+
+0.998627 -0.002161 0.000889
+
+#image size: "height" and "width"
+
+768 1024
+"#;
+
+    #[test]
+    fn test_parse() {
+        let calibration = OcamCalibration::parse(CALIB_RESULTS).unwrap();
+        assert_eq!(calibration.resolution, (1024, 768));
+        let model = &calibration.model;
+        assert_eq!(model.polynomial.len(), 5);
+        assert_eq!(model.polynomial[2], 2.776063e-03);
+        assert_eq!(model.inverse_polynomial, vec![270.5, 150.25, -3.5, 12.0]);
+        assert_eq!((model.cx, model.cy), (489.391537, 374.175359));
+        assert_eq!((model.c, model.d, model.e), (0.998627, -0.002161, 0.000889));
+    }
+    #[test]
+    fn test_roundtrip() {
+        let calibration = OcamCalibration::parse(CALIB_RESULTS).unwrap();
+        let written = calibration.to_calib_results();
+        assert_eq!(OcamCalibration::parse(&written).unwrap(), calibration);
+    }
+    #[test]
+    fn test_invalid() {
+        let truncated = CALIB_RESULTS.replace("4 270.5", "5 270.5");
+        assert!(matches!(
+            OcamCalibration::parse(&truncated),
+            Err(OcamError::Parse(_))
+        ));
+    }
+    #[test]
+    fn test_invalid_image_size() {
+        for size in ["768 -1024", "768.5 1024", "768 1e12", "nan 1024"] {
+            let invalid = CALIB_RESULTS.replace("768 1024", size);
+            assert!(matches!(
+                OcamCalibration::parse(&invalid),
+                Err(OcamError::Parse(_))
+            ));
+        }
+    }
+    #[test]
+    fn test_remap() {
+        let mut calibration = OcamCalibration::parse(CALIB_RESULTS).unwrap();
+        calibration.model.fit_inverse_polynomial(14, 700.0);
+        let camera = calibration.camera_model();
+        assert_eq!(camera.resolution(), Some((1024, 768)));
+        let target = Pinhole::new(100.0, 100.0, 64.0, 48.0, 0.0);
+        let map = RemapMap::new(&camera, calibration.resolution, &target, (128, 96));
+        assert_eq!(map.resolution(), (128, 96));
+        // the principal point of the target looks along the optical axis
+        let center = camera.project(target.unproject(&PixelIndex(64.0, 48.0)));
+        assert!((center.0 - 489.391537).abs() < 1e-9 && (center.1 - 374.175359).abs() < 1e-9);
+    }
+}
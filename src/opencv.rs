@@ -0,0 +1,1067 @@
+//! reading and writing of calibrations in the OpenCV `FileStorage` formats
+//!
+//! only the subset which is used for camera calibrations is supported: top level numbers, strings and
+//! `opencv-matrix` nodes, in both the `%YAML:1.0` and the xml flavour.
+use crate::camera::CameraModel;
+use crate::distortion::{
+    AnyDistortion, CameraDistortion, Division, Fisheye, FovDistortion, Ideal, PinholeDistortion,
+    PlumbBob, Rational, ThinPrism,
+};
+use crate::projection::{AnyProjection, Pinhole};
+
+use nalgebra::DMatrix;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+#[derive(Debug)]
+pub enum OpenCvError {
+    Io(std::io::Error),
+    /// the file is not a valid `FileStorage` document, the string describes the problem
+    Parse(String),
+    MissingNode(String),
+    /// the node exists but has the wrong type or shape
+    InvalidNode(String),
+}
+impl fmt::Display for OpenCvError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OpenCvError::Io(err) => write!(f, "io error: {}", err),
+            OpenCvError::Parse(msg) => write!(f, "parse error: {}", msg),
+            OpenCvError::MissingNode(name) => write!(f, "missing node '{}'", name),
+            OpenCvError::InvalidNode(name) => write!(f, "invalid node '{}'", name),
+        }
+    }
+}
+impl std::error::Error for OpenCvError {}
+impl From<std::io::Error> for OpenCvError {
+    fn from(err: std::io::Error) -> Self {
+        OpenCvError::Io(err)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Node {
+    Number(f64),
+    Text(String),
+    Matrix(DMatrix<f64>),
+}
+
+/// the top level nodes of a `FileStorage` document, in file order
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct FileStorage {
+    nodes: Vec<(String, Node)>,
+}
+
+/// distortion models which are stored as an OpenCV distortion coefficient vector
+pub trait OpenCvDistortion: CameraDistortion + Sized {
+    /// builds the model from the coefficients in OpenCV order, `None` if the count does not match
+    fn from_coefficients(coefficients: &[f64]) -> Option<Self>;
+    fn coefficients(&self) -> Vec<f64>;
+    /// the `distortion_model` node of models which OpenCV does not know, `None` for the OpenCV models
+    fn model(&self) -> Option<&'static str> {
+        None
+    }
+    /// builds the model from the `distortion_model` node and the coefficients
+    fn from_model(model: Option<&str>, coefficients: &[f64]) -> Option<Self> {
+        match model {
+            None => Self::from_coefficients(coefficients),
+            Some(_) => None,
+        }
+    }
+}
+
+/// shorter vectors are padded with zeros, like OpenCV does
+impl OpenCvDistortion for PlumbBob {
+    fn from_coefficients(coefficients: &[f64]) -> Option<Self> {
+        if coefficients.is_empty() || coefficients.len() > 5 {
+            return None;
+        }
+        let mut padded = [0.0; 5];
+        padded[..coefficients.len()].copy_from_slice(coefficients);
+        let [k1, k2, p1, p2, k3] = padded;
+        Some(PlumbBob::new(k1, k2, p1, p2, k3))
+    }
+    fn coefficients(&self) -> Vec<f64> {
+        let PlumbBob { k1, k2, p1, p2, k3 } = *self;
+        vec![k1, k2, p1, p2, k3]
+    }
+}
+impl OpenCvDistortion for Rational {
+    fn from_coefficients(coefficients: &[f64]) -> Option<Self> {
+        match *coefficients {
+            [k1, k2, p1, p2, k3, k4, k5, k6] => Some(Rational {
+                k1,
+                k2,
+                p1,
+                p2,
+                k3,
+                k4,
+                k5,
+                k6,
+            }),
+            _ => None,
+        }
+    }
+    fn coefficients(&self) -> Vec<f64> {
+        let Rational {
+            k1,
+            k2,
+            p1,
+            p2,
+            k3,
+            k4,
+            k5,
+            k6,
+        } = *self;
+        vec![k1, k2, p1, p2, k3, k4, k5, k6]
+    }
+}
+impl OpenCvDistortion for ThinPrism {
+    fn from_coefficients(coefficients: &[f64]) -> Option<Self> {
+        let (tau_x, tau_y) = match coefficients.len() {
+            12 => (0.0, 0.0),
+            14 => (coefficients[12], coefficients[13]),
+            _ => return None,
+        };
+        let rational = Rational::from_coefficients(&coefficients[..8])?;
+        Some(ThinPrism {
+            s1: coefficients[8],
+            s2: coefficients[9],
+            s3: coefficients[10],
+            s4: coefficients[11],
+            tau_x,
+            tau_y,
+            ..ThinPrism::from(rational)
+        })
+    }
+    fn coefficients(&self) -> Vec<f64> {
+        let ThinPrism {
+            k1,
+            k2,
+            p1,
+            p2,
+            k3,
+            k4,
+            k5,
+            k6,
+            s1,
+            s2,
+            s3,
+            s4,
+            tau_x,
+            tau_y,
+        } = *self;
+        vec![k1, k2, p1, p2, k3, k4, k5, k6, s1, s2, s3, s4, tau_x, tau_y]
+    }
+}
+/// not an OpenCV model, stored as a single coefficient with `distortion_model: "fov"`
+impl OpenCvDistortion for FovDistortion {
+    fn from_coefficients(coefficients: &[f64]) -> Option<Self> {
+        match *coefficients {
+            [omega] => Some(FovDistortion::new(omega)),
+            _ => None,
+        }
+    }
+    fn coefficients(&self) -> Vec<f64> {
+        vec![self.omega]
+    }
+    fn model(&self) -> Option<&'static str> {
+        Some("fov")
+    }
+    fn from_model(model: Option<&str>, coefficients: &[f64]) -> Option<Self> {
+        match model {
+            None | Some("fov") => Self::from_coefficients(coefficients),
+            Some(_) => None,
+        }
+    }
+}
+/// not an OpenCV model, stored as `[lambda1, lambda2]` with `distortion_model: "division"`
+impl OpenCvDistortion for Division {
+    fn from_coefficients(coefficients: &[f64]) -> Option<Self> {
+        match *coefficients {
+            [lambda1, lambda2] => Some(Division::new(lambda1, lambda2)),
+            _ => None,
+        }
+    }
+    fn coefficients(&self) -> Vec<f64> {
+        vec![self.lambda1, self.lambda2]
+    }
+    fn model(&self) -> Option<&'static str> {
+        Some("division")
+    }
+    fn from_model(model: Option<&str>, coefficients: &[f64]) -> Option<Self> {
+        match model {
+            None | Some("division") => Self::from_coefficients(coefficients),
+            Some(_) => None,
+        }
+    }
+}
+/// picks the OpenCV model from the number of coefficients like OpenCV does, the other models are
+/// only selected by the `distortion_model` node
+impl OpenCvDistortion for PinholeDistortion {
+    fn from_coefficients(coefficients: &[f64]) -> Option<Self> {
+        match coefficients.len() {
+            12 | 14 => ThinPrism::from_coefficients(coefficients).map(PinholeDistortion::ThinPrism),
+            8 => Rational::from_coefficients(coefficients).map(PinholeDistortion::Rational),
+            _ => PlumbBob::from_coefficients(coefficients).map(PinholeDistortion::PlumbBob),
+        }
+    }
+    fn coefficients(&self) -> Vec<f64> {
+        match self {
+            PinholeDistortion::ThinPrism(distortion) => distortion.coefficients(),
+            PinholeDistortion::Rational(distortion) => distortion.coefficients(),
+            PinholeDistortion::PlumbBob(distortion) => distortion.coefficients(),
+            PinholeDistortion::Fov(distortion) => distortion.coefficients(),
+            PinholeDistortion::Division(distortion) => distortion.coefficients(),
+        }
+    }
+    fn model(&self) -> Option<&'static str> {
+        match self {
+            PinholeDistortion::Fov(distortion) => distortion.model(),
+            PinholeDistortion::Division(distortion) => distortion.model(),
+            _ => None,
+        }
+    }
+    fn from_model(model: Option<&str>, coefficients: &[f64]) -> Option<Self> {
+        match model {
+            None => Self::from_coefficients(coefficients),
+            Some("fov") => {
+                FovDistortion::from_model(model, coefficients).map(PinholeDistortion::Fov)
+            }
+            Some("division") => {
+                Division::from_model(model, coefficients).map(PinholeDistortion::Division)
+            }
+            Some(_) => None,
+        }
+    }
+}
+impl OpenCvDistortion for Fisheye {
+    fn from_coefficients(coefficients: &[f64]) -> Option<Self> {
+        match *coefficients {
+            [k1, k2, k3, k4] => Some(Fisheye {
+                k1,
+                k2,
+                k3,
+                k4,
+                s: 0.0,
+            }),
+            _ => None,
+        }
+    }
+    fn coefficients(&self) -> Vec<f64> {
+        vec![self.k1, self.k2, self.k3, self.k4]
+    }
+    /// the 4 coefficients of `cv::fisheye` can not be told apart from a plumb bob by their count
+    fn model(&self) -> Option<&'static str> {
+        Some("fisheye")
+    }
+    fn from_model(model: Option<&str>, coefficients: &[f64]) -> Option<Self> {
+        match model {
+            None | Some("fisheye") => Self::from_coefficients(coefficients),
+            Some(_) => None,
+        }
+    }
+}
+/// like `PinholeDistortion`, the fisheye model is selected by the `distortion_model` node and an empty
+/// coefficient vector is the ideal camera
+impl OpenCvDistortion for AnyDistortion {
+    fn from_coefficients(coefficients: &[f64]) -> Option<Self> {
+        Self::from_model(None, coefficients)
+    }
+    fn coefficients(&self) -> Vec<f64> {
+        match self {
+            AnyDistortion::Ideal => Ideal {}.coefficients(),
+            AnyDistortion::PlumbBob(distortion) => distortion.coefficients(),
+            AnyDistortion::Rational(distortion) => distortion.coefficients(),
+            AnyDistortion::ThinPrism(distortion) => distortion.coefficients(),
+            AnyDistortion::Fisheye(distortion) => distortion.coefficients(),
+            AnyDistortion::Fov(distortion) => distortion.coefficients(),
+            AnyDistortion::Division(distortion) => distortion.coefficients(),
+        }
+    }
+    fn model(&self) -> Option<&'static str> {
+        match self {
+            AnyDistortion::Fisheye(distortion) => distortion.model(),
+            AnyDistortion::Fov(distortion) => distortion.model(),
+            AnyDistortion::Division(distortion) => distortion.model(),
+            _ => None,
+        }
+    }
+    fn from_model(model: Option<&str>, coefficients: &[f64]) -> Option<Self> {
+        match model {
+            None if coefficients.is_empty() => Some(AnyDistortion::Ideal),
+            Some("fisheye") => Fisheye::from_model(model, coefficients).map(AnyDistortion::Fisheye),
+            _ => PinholeDistortion::from_model(model, coefficients).map(AnyDistortion::from),
+        }
+    }
+}
+impl OpenCvDistortion for Ideal {
+    fn from_coefficients(coefficients: &[f64]) -> Option<Self> {
+        coefficients.iter().all(|c| *c == 0.0).then_some(Ideal {})
+    }
+    fn coefficients(&self) -> Vec<f64> {
+        Vec::new()
+    }
+}
+
+impl FileStorage {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// reads a file, the format is detected from the content
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, OpenCvError> {
+        Self::parse(&fs::read_to_string(path)?)
+    }
+    /// writes the file as xml if the extension is `.xml`, otherwise as yaml
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), OpenCvError> {
+        let path = path.as_ref();
+        let contents = match path.extension().and_then(|ext| ext.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("xml") => self.to_xml(),
+            _ => self.to_yaml(),
+        };
+        fs::write(path, contents)?;
+        Ok(())
+    }
+    pub fn parse(contents: &str) -> Result<Self, OpenCvError> {
+        if contents.trim_start().starts_with('<') {
+            Self::from_xml(contents)
+        } else {
+            Self::from_yaml(contents)
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Node> {
+        self.nodes
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, node)| node)
+    }
+    /// inserts or replaces a node, new nodes are appended at the end
+    pub fn set(&mut self, name: &str, node: Node) {
+        match self.nodes.iter_mut().find(|(key, _)| key == name) {
+            Some((_, existing)) => *existing = node,
+            None => self.nodes.push((name.to_string(), node)),
+        }
+    }
+    pub fn remove(&mut self, name: &str) -> Option<Node> {
+        let idx = self.nodes.iter().position(|(key, _)| key == name)?;
+        Some(self.nodes.remove(idx).1)
+    }
+    pub fn nodes(&self) -> impl Iterator<Item = (&str, &Node)> {
+        self.nodes.iter().map(|(key, node)| (key.as_str(), node))
+    }
+    fn require(&self, name: &str) -> Result<&Node, OpenCvError> {
+        self.get(name)
+            .ok_or_else(|| OpenCvError::MissingNode(name.to_string()))
+    }
+    pub fn number(&self, name: &str) -> Result<f64, OpenCvError> {
+        match self.require(name)? {
+            Node::Number(value) => Ok(*value),
+            _ => Err(OpenCvError::InvalidNode(name.to_string())),
+        }
+    }
+    pub fn matrix(&self, name: &str) -> Result<&DMatrix<f64>, OpenCvError> {
+        match self.require(name)? {
+            Node::Matrix(matrix) => Ok(matrix),
+            _ => Err(OpenCvError::InvalidNode(name.to_string())),
+        }
+    }
+
+    /// reads the `camera_matrix` node
+    pub fn pinhole(&self) -> Result<Pinhole, OpenCvError> {
+        let m = self.matrix("camera_matrix")?;
+        if m.shape() != (3, 3) {
+            return Err(OpenCvError::InvalidNode("camera_matrix".to_string()));
+        }
+        Ok(Pinhole::new(
+            m[(0, 0)],
+            m[(1, 1)],
+            m[(0, 2)],
+            m[(1, 2)],
+            m[(0, 1)],
+        ))
+    }
+    pub fn set_pinhole(&mut self, pinhole: &Pinhole) {
+        let Pinhole {
+            fx,
+            fy,
+            cx,
+            cy,
+            skew,
+        } = *pinhole;
+        let m = DMatrix::from_row_slice(3, 3, &[fx, skew, cx, 0.0, fy, cy, 0.0, 0.0, 1.0]);
+        self.set("camera_matrix", Node::Matrix(m));
+    }
+    /// reads the `distortion` node, `distortion_coefficients` is accepted as well. models which are
+    /// not part of OpenCV are selected by the `distortion_model` node
+    pub fn distortion<V: OpenCvDistortion>(&self) -> Result<V, OpenCvError> {
+        let name = match self.get("distortion") {
+            Some(_) => "distortion",
+            None => "distortion_coefficients",
+        };
+        let model = match self.get("distortion_model") {
+            Some(Node::Text(model)) => Some(model.as_str()),
+            Some(_) => return Err(OpenCvError::InvalidNode("distortion_model".to_string())),
+            None => None,
+        };
+        let m = self.matrix(name)?;
+        V::from_model(model, m.transpose().as_slice())
+            .ok_or_else(|| OpenCvError::InvalidNode(name.to_string()))
+    }
+    pub fn set_distortion<V: OpenCvDistortion>(&mut self, distortion: &V) {
+        let coefficients = distortion.coefficients();
+        let m = DMatrix::from_row_slice(1, coefficients.len(), &coefficients);
+        self.set("distortion", Node::Matrix(m));
+        match distortion.model() {
+            Some(model) => self.set("distortion_model", Node::Text(model.to_string())),
+            None => {
+                self.remove("distortion_model");
+            }
+        }
+    }
+    /// reads the `image_width` and `image_height` nodes, `None` if the file lacks one of them
+    pub fn resolution(&self) -> Result<Option<(u32, u32)>, OpenCvError> {
+        if self.get("image_width").is_none() || self.get("image_height").is_none() {
+            return Ok(None);
+        }
+        let size = |name: &str| {
+            let value = self.number(name)?;
+            if value < 0.0 || value.fract() != 0.0 || value > u32::MAX as f64 {
+                return Err(OpenCvError::InvalidNode(name.to_string()));
+            }
+            Ok(value as u32)
+        };
+        Ok(Some((size("image_width")?, size("image_height")?)))
+    }
+    pub fn set_resolution(&mut self, (width, height): (u32, u32)) {
+        self.set("image_width", Node::Number(width as f64));
+        self.set("image_height", Node::Number(height as f64));
+    }
+    /// reads the camera, the resolution is taken from `image_width` and `image_height` if present
+    pub fn camera_model<V: OpenCvDistortion>(
+        &self,
+    ) -> Result<CameraModel<Pinhole, V>, OpenCvError> {
+        let model = CameraModel::new(self.pinhole()?, self.distortion()?);
+        Ok(match self.resolution()? {
+            Some(resolution) => model.with_resolution(resolution),
+            None => model,
+        })
+    }
+    /// reads the camera with the distortion model selected at runtime, the projection of OpenCV files is
+    /// always a pinhole
+    pub fn any_camera_model(
+        &self,
+    ) -> Result<CameraModel<AnyProjection, AnyDistortion>, OpenCvError> {
+        let model = CameraModel::new(AnyProjection::Pinhole(self.pinhole()?), self.distortion()?);
+        Ok(match self.resolution()? {
+            Some(resolution) => model.with_resolution(resolution),
+            None => model,
+        })
+    }
+    pub fn set_camera_model<V: OpenCvDistortion>(&mut self, model: &CameraModel<Pinhole, V>) {
+        self.set_pinhole(model.projection());
+        self.set_distortion(model.distortion());
+        if let Some(resolution) = model.resolution() {
+            self.set_resolution(resolution);
+        }
+    }
+
+    pub fn from_yaml(contents: &str) -> Result<Self, OpenCvError> {
+        let mut lines = contents.lines().map(strip_comment).peekable();
+        match lines.next() {
+            Some(header) if header.trim_start().starts_with("%YAML") => (),
+            _ => return Err(OpenCvError::Parse("missing %YAML header".to_string())),
+        }
+        let mut storage = Self::new();
+        while let Some(line) = lines.next() {
+            if line.trim().is_empty() || line.trim() == "---" {
+                continue;
+            }
+            if line.starts_with(char::is_whitespace) {
+                return Err(OpenCvError::Parse(format!(
+                    "unexpected indentation: '{}'",
+                    line
+                )));
+            }
+            let (key, value) = split_key(line)?;
+            // collect the indented block which belongs to this key
+            let mut block = Vec::new();
+            while let Some(next) = lines.peek() {
+                if next.trim().is_empty() || next.starts_with(char::is_whitespace) {
+                    block.push(lines.next().unwrap());
+                } else {
+                    break;
+                }
+            }
+            if value.starts_with("!!opencv-matrix") {
+                let matrix = parse_yaml_matrix(key, &block)?;
+                storage.set(key, Node::Matrix(matrix));
+            } else if !value.is_empty() {
+                storage.set(key, parse_scalar(value));
+            }
+            // other nested structures are not needed for calibrations and are skipped
+        }
+        Ok(storage)
+    }
+    pub fn to_yaml(&self) -> String {
+        let mut out = String::from("%YAML:1.0\n---\n");
+        for (key, node) in &self.nodes {
+            match node {
+                Node::Number(value) => out.push_str(&format!("{}: {}\n", key, value)),
+                Node::Text(text) => out.push_str(&format!("{}: {}\n", key, quote(text))),
+                Node::Matrix(m) => {
+                    out.push_str(&format!("{}: !!opencv-matrix\n", key));
+                    out.push_str(&format!("   rows: {}\n", m.nrows()));
+                    out.push_str(&format!("   cols: {}\n", m.ncols()));
+                    out.push_str("   dt: d\n");
+                    out.push_str(&format!("   data: [ {} ]\n", row_major(m).join(", ")));
+                }
+            }
+        }
+        out
+    }
+
+    pub fn from_xml(contents: &str) -> Result<Self, OpenCvError> {
+        let mut parser = XmlParser::new(contents);
+        parser.skip_prolog();
+        let root = parser.element()?;
+        if root.name != "opencv_storage" {
+            return Err(OpenCvError::Parse(format!(
+                "expected <opencv_storage>, found <{}>",
+                root.name
+            )));
+        }
+        let mut storage = Self::new();
+        for child in &root.children {
+            if child.type_id.as_deref() == Some("opencv-matrix") {
+                let matrix = parse_xml_matrix(child)?;
+                storage.set(&child.name, Node::Matrix(matrix));
+            } else if child.children.is_empty() {
+                storage.set(&child.name, parse_scalar(&unescape_xml(&child.text)));
+            }
+        }
+        Ok(storage)
+    }
+    pub fn to_xml(&self) -> String {
+        let mut out = String::from("<?xml version=\"1.0\"?>\n<opencv_storage>\n");
+        for (key, node) in &self.nodes {
+            match node {
+                Node::Number(value) => out.push_str(&format!("<{0}>{1}</{0}>\n", key, value)),
+                Node::Text(text) => {
+                    out.push_str(&format!("<{0}>{1}</{0}>\n", key, escape_xml(&quote(text))))
+                }
+                Node::Matrix(m) => {
+                    out.push_str(&format!("<{} type_id=\"opencv-matrix\">\n", key));
+                    out.push_str(&format!("  <rows>{}</rows>\n", m.nrows()));
+                    out.push_str(&format!("  <cols>{}</cols>\n", m.ncols()));
+                    out.push_str("  <dt>d</dt>\n");
+                    out.push_str(&format!(
+                        "  <data>\n    {}</data></{}>\n",
+                        row_major(m).join(" "),
+                        key
+                    ));
+                }
+            }
+        }
+        out.push_str("</opencv_storage>\n");
+        out
+    }
+}
+
+/// reads a `Pinhole` camera with the distortion model `V` from an OpenCV yaml or xml file
+pub fn read_camera_model<V: OpenCvDistortion, P: AsRef<Path>>(
+    path: P,
+) -> Result<CameraModel<Pinhole, V>, OpenCvError> {
+    FileStorage::open(path)?.camera_model()
+}
+
+/// writes the camera as an OpenCV yaml or xml file, depending on the extension
+pub fn write_camera_model<V: OpenCvDistortion, P: AsRef<Path>>(
+    path: P,
+    model: &CameraModel<Pinhole, V>,
+) -> Result<(), OpenCvError> {
+    let mut storage = FileStorage::new();
+    storage.set_camera_model(model);
+    storage.save(path)
+}
+
+/// cuts off a comment, a `#` inside of a quoted string belongs to the string
+fn strip_comment(line: &str) -> &str {
+    let mut quoted = false;
+    let mut escaped = false;
+    for (idx, c) in line.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if quoted => escaped = true,
+            '"' => quoted = !quoted,
+            '#' if !quoted => return &line[..idx],
+            _ => (),
+        }
+    }
+    line
+}
+
+/// a double quoted string, `"` and `\` are escaped with a backslash
+fn quote(text: &str) -> String {
+    format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+fn unquote(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => out.extend(chars.next()),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+fn unescape_xml(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+fn split_key(line: &str) -> Result<(&str, &str), OpenCvError> {
+    let (key, value) = line
+        .split_once(':')
+        .ok_or_else(|| OpenCvError::Parse(format!("expected 'key: value', found '{}'", line)))?;
+    Ok((key.trim(), value.trim()))
+}
+
+fn parse_scalar(value: &str) -> Node {
+    let value = value.trim();
+    if let Some(text) = value.strip_prefix('"').and_then(|v| v.strip_suffix('"')) {
+        return Node::Text(unquote(text));
+    }
+    match value.parse::<f64>() {
+        Ok(number) => Node::Number(number),
+        Err(_) => Node::Text(value.to_string()),
+    }
+}
+
+fn parse_numbers(name: &str, data: &str) -> Result<Vec<f64>, OpenCvError> {
+    data.split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|s| !s.is_empty())
+        .map(|s| {
+            s.parse::<f64>()
+                .map_err(|_| OpenCvError::InvalidNode(name.to_string()))
+        })
+        .collect()
+}
+
+fn build_matrix(
+    name: &str,
+    rows: Option<&str>,
+    cols: Option<&str>,
+    data: Option<&str>,
+) -> Result<DMatrix<f64>, OpenCvError> {
+    let invalid = || OpenCvError::InvalidNode(name.to_string());
+    let rows: usize = rows
+        .and_then(|r| r.trim().parse().ok())
+        .ok_or_else(invalid)?;
+    let cols: usize = cols
+        .and_then(|c| c.trim().parse().ok())
+        .ok_or_else(invalid)?;
+    let data = parse_numbers(name, data.ok_or_else(invalid)?)?;
+    if data.len() != rows * cols {
+        return Err(invalid());
+    }
+    Ok(DMatrix::from_row_slice(rows, cols, &data))
+}
+
+fn parse_yaml_matrix(name: &str, block: &[&str]) -> Result<DMatrix<f64>, OpenCvError> {
+    let mut rows = None;
+    let mut cols = None;
+    let mut data = None;
+    let mut lines = block.iter();
+    while let Some(line) = lines.next() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let (key, value) = split_key(line)?;
+        match key {
+            "rows" => rows = Some(value.to_string()),
+            "cols" => cols = Some(value.to_string()),
+            "data" => {
+                // the data list may continue over several lines until the closing bracket
+                let mut list = value.to_string();
+                while !list.contains(']') {
+                    match lines.next() {
+                        Some(line) => list.push_str(line),
+                        None => return Err(OpenCvError::InvalidNode(name.to_string())),
+                    }
+                }
+                data = Some(
+                    list.trim()
+                        .trim_start_matches('[')
+                        .trim_end_matches(']')
+                        .to_string(),
+                );
+            }
+            _ => (),
+        }
+    }
+    build_matrix(name, rows.as_deref(), cols.as_deref(), data.as_deref())
+}
+
+fn parse_xml_matrix(element: &XmlElement) -> Result<DMatrix<f64>, OpenCvError> {
+    let child = |name: &str| {
+        element
+            .children
+            .iter()
+            .find(|c| c.name == name)
+            .map(|c| c.text.as_str())
+    };
+    build_matrix(&element.name, child("rows"), child("cols"), child("data"))
+}
+
+fn row_major(m: &DMatrix<f64>) -> Vec<String> {
+    m.transpose().iter().map(|v| format!("{:?}", v)).collect()
+}
+
+struct XmlElement {
+    name: String,
+    type_id: Option<String>,
+    text: String,
+    children: Vec<XmlElement>,
+}
+
+/// a minimal xml reader, sufficient for the documents written by OpenCV
+struct XmlParser<'a> {
+    rest: &'a str,
+}
+impl<'a> XmlParser<'a> {
+    fn new(contents: &'a str) -> Self {
+        Self { rest: contents }
+    }
+    fn error(&self, msg: &str) -> OpenCvError {
+        let context: String = self.rest.chars().take(20).collect();
+        OpenCvError::Parse(format!("{} at '{}'", msg, context))
+    }
+    /// skips whitespace, comments and processing instructions
+    fn skip_prolog(&mut self) {
+        loop {
+            self.rest = self.rest.trim_start();
+            let end = if self.rest.starts_with("<?") {
+                self.rest.find("?>").map(|idx| idx + 2)
+            } else if self.rest.starts_with("<!--") {
+                self.rest.find("-->").map(|idx| idx + 3)
+            } else {
+                None
+            };
+            match end {
+                Some(end) => self.rest = &self.rest[end..],
+                None => return,
+            }
+        }
+    }
+    fn element(&mut self) -> Result<XmlElement, OpenCvError> {
+        self.skip_prolog();
+        if !self.rest.starts_with('<') {
+            return Err(self.error("expected element"));
+        }
+        let end = self
+            .rest
+            .find('>')
+            .ok_or_else(|| self.error("unclosed tag"))?;
+        let tag = &self.rest[1..end];
+        self.rest = &self.rest[end + 1..];
+        let (tag, empty) = match tag.strip_suffix('/') {
+            Some(tag) => (tag, true),
+            None => (tag, false),
+        };
+        let mut parts = tag.split_whitespace();
+        let name = parts
+            .next()
+            .ok_or_else(|| self.error("empty tag"))?
+            .to_string();
+        let type_id = parts.find_map(|attr| {
+            attr.strip_prefix("type_id=")
+                .map(|value| value.trim_matches(|c| c == '"' || c == '\'').to_string())
+        });
+        let mut element = XmlElement {
+            name,
+            type_id,
+            text: String::new(),
+            children: Vec::new(),
+        };
+        if empty {
+            return Ok(element);
+        }
+        let closing = format!("</{}>", element.name);
+        loop {
+            let next = self
+                .rest
+                .find('<')
+                .ok_or_else(|| self.error("unclosed element"))?;
+            element.text.push_str(&self.rest[..next]);
+            self.rest = &self.rest[next..];
+            if let Some(rest) = self.rest.strip_prefix(closing.as_str()) {
+                self.rest = rest;
+                break;
+            } else if self.rest.starts_with("<!--") || self.rest.starts_with("<?") {
+                self.skip_prolog();
+            } else {
+                element.children.push(self.element()?);
+            }
+        }
+        element.text = element.text.trim().to_string();
+        Ok(element)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const XML: &str = r#"<?xml version="1.0"?>
+<opencv_storage>
+<image_width>640</image_width>
+<camera_matrix type_id="opencv-matrix">
+  <rows>3</rows>
+  <cols>3</cols>
+  <dt>d</dt>
+  <data>
+    500. 0. 320. 0. 510. 240.
+    0. 0. 1.</data></camera_matrix>
+<!-- coefficients in opencv order -->
+<distortion_coefficients type_id="opencv-matrix">
+  <rows>5</rows>
+  <cols>1</cols>
+  <dt>d</dt>
+  <data>
+    -0.2 0.05 1.e-03 -2.e-03 0.</data></distortion_coefficients>
+</opencv_storage>
+"#;
+
+    #[test]
+    fn test_read_yaml() {
+        let storage = FileStorage::parse(include_str!("../tests/camera.yaml")).unwrap();
+        let model = storage.camera_model::<PlumbBob>().unwrap();
+        let projection = model.projection();
+        assert_eq!(projection.fx, 1244.617161547647);
+        assert_eq!(projection.fy, 930.993392665601);
+        assert_eq!(projection.cx, 2016.0);
+        assert_eq!(projection.cy, 1508.0);
+        assert_eq!(projection.skew, 0.0);
+        assert_eq!(*model.distortion(), PlumbBob::new(0.1, 0.1, 0.2, 0.1, 0.0));
+    }
+    #[test]
+    fn test_read_xml() {
+        let storage = FileStorage::parse(XML).unwrap();
+        assert_eq!(storage.number("image_width").unwrap(), 640.0);
+        let model = storage.camera_model::<PlumbBob>().unwrap();
+        assert_eq!(model.projection().fy, 510.0);
+        assert_eq!(
+            *model.distortion(),
+            PlumbBob::new(-0.2, 0.05, 0.001, -0.002, 0.0)
+        );
+    }
+    #[test]
+    fn test_resolution() {
+        // the example has no image height
+        let mut storage = FileStorage::parse(XML).unwrap();
+        assert_eq!(storage.resolution().unwrap(), None);
+        storage.set("image_height", Node::Number(480.0));
+        let model = storage.camera_model::<PlumbBob>().unwrap();
+        assert_eq!(model.resolution(), Some((640, 480)));
+
+        let mut written = FileStorage::new();
+        written.set_camera_model(&model);
+        assert_eq!(written.number("image_height").unwrap(), 480.0);
+        let yaml = FileStorage::parse(&written.to_yaml()).unwrap();
+        assert_eq!(yaml.resolution().unwrap(), Some((640, 480)));
+
+        storage.set("image_height", Node::Number(-480.0));
+        assert!(matches!(
+            storage.resolution(),
+            Err(OpenCvError::InvalidNode(name)) if name == "image_height"
+        ));
+    }
+    #[test]
+    fn test_multiline_yaml_data() {
+        let yaml = "%YAML:1.0\n---\nname: \"left\"\nd: !!opencv-matrix\n   rows: 1\n   cols: 4\n   dt: d\n   data: [ 0.1, 0.2,\n       0.3, 0.4 ]\n";
+        let storage = FileStorage::parse(yaml).unwrap();
+        assert_eq!(storage.get("name"), Some(&Node::Text("left".to_string())));
+        assert_eq!(
+            storage.matrix("d").unwrap().as_slice(),
+            &[0.1, 0.2, 0.3, 0.4]
+        );
+    }
+    #[test]
+    fn test_wrong_coefficient_count() {
+        let storage = FileStorage::parse(XML).unwrap();
+        assert!(matches!(
+            storage.distortion::<Fisheye>(),
+            Err(OpenCvError::InvalidNode(_))
+        ));
+    }
+    #[test]
+    fn test_select_pinhole_distortion() {
+        let storage = FileStorage::parse(include_str!("../tests/camera.yaml")).unwrap();
+        assert!(matches!(
+            storage.distortion::<PinholeDistortion>().unwrap(),
+            PinholeDistortion::PlumbBob(_)
+        ));
+        let mut storage = FileStorage::new();
+        let coefficients = [0.1, 0.01, 0.001, -0.002, 0.0001, 0.05, 0.005, 0.0005];
+        storage.set(
+            "distortion",
+            Node::Matrix(DMatrix::from_row_slice(8, 1, &coefficients)),
+        );
+        let distortion = storage.distortion::<PinholeDistortion>().unwrap();
+        assert!(matches!(distortion, PinholeDistortion::Rational(_)));
+        assert_eq!(distortion.coefficients(), coefficients);
+
+        let mut coefficients = coefficients.to_vec();
+        coefficients.extend([0.002, -0.0005, -0.001, 0.0003]);
+        storage.set(
+            "distortion",
+            Node::Matrix(DMatrix::from_row_slice(1, 12, &coefficients)),
+        );
+        let distortion = storage.distortion::<PinholeDistortion>().unwrap();
+        assert!(matches!(distortion, PinholeDistortion::ThinPrism(t) if t.tau_x == 0.0));
+        coefficients.extend([0.0, 0.0]);
+        assert_eq!(distortion.coefficients(), coefficients);
+
+        storage.set("distortion", Node::Matrix(DMatrix::from_element(1, 1, 0.9)));
+        storage.set("distortion_model", Node::Text("fov".to_string()));
+        let distortion = storage.distortion::<PinholeDistortion>().unwrap();
+        assert_eq!(distortion, PinholeDistortion::Fov(FovDistortion::new(0.9)));
+        storage.set("distortion_model", Node::Text("kannala_brandt".to_string()));
+        assert!(storage.distortion::<PinholeDistortion>().is_err());
+    }
+    #[test]
+    fn test_short_coefficients() {
+        // OpenCV reads a single coefficient as k1 of the plumb bob model
+        let mut storage = FileStorage::parse(XML).unwrap();
+        storage.set(
+            "distortion_coefficients",
+            Node::Matrix(DMatrix::from_element(1, 1, -0.2)),
+        );
+        let expected = PlumbBob::new(-0.2, 0.0, 0.0, 0.0, 0.0);
+        let model = storage.camera_model::<PinholeDistortion>().unwrap();
+        assert_eq!(*model.distortion(), PinholeDistortion::PlumbBob(expected));
+        // the fov model is an explicit opt-in
+        let model = storage.camera_model::<FovDistortion>().unwrap();
+        assert_eq!(*model.distortion(), FovDistortion::new(-0.2));
+
+        // and two coefficients are k1 and k2, not the division model
+        storage.set(
+            "distortion_coefficients",
+            Node::Matrix(DMatrix::from_row_slice(2, 1, &[-0.2, 0.05])),
+        );
+        let expected = PlumbBob::new(-0.2, 0.05, 0.0, 0.0, 0.0);
+        let model = storage.camera_model::<PinholeDistortion>().unwrap();
+        assert_eq!(*model.distortion(), PinholeDistortion::PlumbBob(expected));
+        let model = storage.camera_model::<Division>().unwrap();
+        assert_eq!(*model.distortion(), Division::new(-0.2, 0.05));
+        storage.set("distortion_model", Node::Text("division".to_string()));
+        let model = storage.camera_model::<PinholeDistortion>().unwrap();
+        assert_eq!(
+            *model.distortion(),
+            PinholeDistortion::Division(Division::new(-0.2, 0.05))
+        );
+
+        let mut written = FileStorage::new();
+        written.set_distortion(&PinholeDistortion::Fov(FovDistortion::new(0.9)));
+        let yaml = FileStorage::parse(&written.to_yaml()).unwrap();
+        assert_eq!(
+            yaml.distortion::<PinholeDistortion>().unwrap(),
+            PinholeDistortion::Fov(FovDistortion::new(0.9))
+        );
+        written.set_distortion(&expected);
+        assert_eq!(written.get("distortion_model"), None);
+    }
+    #[test]
+    fn test_any_camera_model() {
+        let storage = FileStorage::parse(include_str!("../tests/camera.yaml")).unwrap();
+        let model = storage.any_camera_model().unwrap();
+        assert!(matches!(model.projection(), AnyProjection::Pinhole(p) if p.cx == 2016.0));
+        assert_eq!(
+            *model.distortion(),
+            AnyDistortion::PlumbBob(PlumbBob::new(0.1, 0.1, 0.2, 0.1, 0.0))
+        );
+
+        // four coefficients are a plumb bob unless the file says otherwise
+        let fisheye = Fisheye {
+            k1: 0.1,
+            k2: -0.01,
+            k3: 0.001,
+            k4: -0.0001,
+            s: 0.0,
+        };
+        let mut written = FileStorage::new();
+        written.set_pinhole(&Pinhole::new(300.0, 300.0, 320.0, 240.0, 0.0));
+        written.set_distortion(&fisheye);
+        let yaml = FileStorage::parse(&written.to_yaml()).unwrap();
+        let model = yaml.any_camera_model().unwrap();
+        assert_eq!(*model.distortion(), AnyDistortion::Fisheye(fisheye));
+        assert!(matches!(
+            yaml.distortion::<PinholeDistortion>(),
+            Err(OpenCvError::InvalidNode(_))
+        ));
+
+        written.set_distortion(&AnyDistortion::Ideal);
+        let model = written.any_camera_model().unwrap();
+        assert_eq!(*model.distortion(), AnyDistortion::Ideal);
+    }
+    #[test]
+    fn test_quoted_text() {
+        let yaml = "%YAML:1.0\nname: \"left # 1\" # the camera\nplain: left\n";
+        let storage = FileStorage::parse(yaml).unwrap();
+        assert_eq!(
+            storage.get("name"),
+            Some(&Node::Text("left # 1".to_string()))
+        );
+        assert_eq!(storage.get("plain"), Some(&Node::Text("left".to_string())));
+
+        let mut storage = FileStorage::new();
+        let time = r#"Mon 12:00:01 # "cam\0" <a & b>"#;
+        storage.set("calibration_time", Node::Text(time.to_string()));
+        // a quoted number stays a string
+        storage.set("serial", Node::Text("0123".to_string()));
+        storage.set_camera_model(&CameraModel::new(
+            Pinhole::new(500.0, 500.0, 320.0, 240.0, 0.0),
+            PlumbBob::new(-0.2, 0.05, 0.0, 0.0, 0.0),
+        ));
+        assert_eq!(FileStorage::parse(&storage.to_yaml()).unwrap(), storage);
+        assert_eq!(FileStorage::parse(&storage.to_xml()).unwrap(), storage);
+
+        let path = std::env::temp_dir().join("camera_models_quoted_text.yaml");
+        let mut written = storage.clone();
+        written.set_resolution((640, 480));
+        written.save(&path).unwrap();
+        let read = FileStorage::open(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(read, written);
+    }
+    #[test]
+    fn test_roundtrip() {
+        let mut storage = FileStorage::new();
+        storage.set("image_width", Node::Number(640.0));
+        storage.set("camera_name", Node::Text("left".to_string()));
+        storage.set_pinhole(&Pinhole::new(500.5, 501.25, 319.75, 240.125, 0.5));
+        storage.set_distortion(&Fisheye {
+            k1: 0.1,
+            k2: -0.01,
+            k3: 0.001,
+            k4: 1.0 / 3.0,
+            s: 0.0,
+        });
+        assert_eq!(FileStorage::parse(&storage.to_yaml()).unwrap(), storage);
+        assert_eq!(FileStorage::parse(&storage.to_xml()).unwrap(), storage);
+    }
+}
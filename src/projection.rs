@@ -1,11 +1,38 @@
 // use crate::base::{CameraRay, Point, Point2, Transform, PixelIndex};
-use crate::{
-    camera::{CameraRay, PixelIndex},
+use crate::camera::{CameraRay, PixelIndex};
+
+use crate::distortion::{DistortionError, IterativeSolver};
+
+use nalgebra::{
+    DMatrix, DVector, Matrix2, Matrix2x3, Matrix3, Matrix3x2, RealField, Rotation3, Vector2,
+    Vector3,
 };
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub struct Pinhole<T = f64> {
+    pub fx: T,
+    pub fy: T,
+    pub cx: T,
+    pub cy: T,
+    /// named `s` by the python calibration script
+    #[serde(alias = "s")]
+    pub skew: T,
+}
 
-use nalgebra::{Matrix2x3, Rotation3, Vector3};
-#[derive(Debug, Clone, Copy)]
-pub struct Pinhole {
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub struct Fisheye<T = f64> {
+    pub fx: T,
+    pub fy: T,
+    pub cx: T,
+    pub cy: T,
+    pub skew: T,
+}
+
+/// stereographic projection `r = 2 tan(theta / 2)`, valid for angles below 180° to the optical axis
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub struct Stereographic {
     pub fx: f64,
     pub fy: f64,
     pub cx: f64,
@@ -13,8 +40,9 @@ pub struct Pinhole {
     pub skew: f64,
 }
 
-#[derive(Debug, Clone, Copy)]
-pub struct Fisheye {
+/// equisolid angle projection `r = 2 sin(theta / 2)`, valid up to 180° to the optical axis
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub struct Equisolid {
     pub fx: f64,
     pub fy: f64,
     pub cx: f64,
@@ -22,8 +50,155 @@ pub struct Fisheye {
     pub skew: f64,
 }
 
-impl Pinhole {
-    pub fn new(fx: f64, fy: f64, cx: f64, cy: f64, skew: f64) -> Self {
+/// orthographic projection `r = sin(theta)`, valid up to 90° to the optical axis
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub struct Orthographic {
+    pub fx: f64,
+    pub fy: f64,
+    pub cx: f64,
+    pub cy: f64,
+    pub skew: f64,
+}
+
+/// the unified camera model (Mei), a projection onto a unit sphere followed by a pinhole projection from a
+/// center which is shifted by `xi` along the optical axis
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub struct UnifiedCamera {
+    pub fx: f64,
+    pub fy: f64,
+    pub cx: f64,
+    pub cy: f64,
+    pub xi: f64,
+}
+
+/// the extended unified camera model (Khomenko et al.), the sphere of the unified model becomes an ellipsoid
+/// with the shape parameter `beta`, `alpha` in [0, 1] is the relative position of the projection center
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub struct ExtendedUnifiedCamera {
+    pub fx: f64,
+    pub fy: f64,
+    pub cx: f64,
+    pub cy: f64,
+    pub alpha: f64,
+    pub beta: f64,
+}
+
+/// the double sphere model (Usenko et al.), the ray is projected onto two unit spheres which are `xi` apart and
+/// then through a pinhole which is shifted by `alpha`, both directions have a closed form
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub struct DoubleSphere {
+    pub fx: f64,
+    pub fy: f64,
+    pub cx: f64,
+    pub cy: f64,
+    pub xi: f64,
+    pub alpha: f64,
+}
+
+/// the polynomial omnidirectional model of Scaramuzza (OCamCalib), lens and projection in one model
+///
+/// the coefficients use the OCamCalib frame, where x is along the image rows, y along the columns and z points
+/// out of the lens, i.e. against our viewing direction. the center is given as column `cx` and row `cy`.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Scaramuzza {
+    /// `pol`, the height of the unprojected point over the radius in pixels, used by `unproject`
+    pub polynomial: Vec<f64>,
+    /// `invpol`, the radius in pixels over the elevation angle, used by `project`
+    pub inverse_polynomial: Vec<f64>,
+    pub cx: f64,
+    pub cy: f64,
+    /// the affine parameters of the sensor
+    pub c: f64,
+    pub d: f64,
+    pub e: f64,
+}
+
+/// the generic central model of Schöps et al., a cubic B-spline grid of bearing directions over the image
+///
+/// control point `(i, j)` is stored at `grid[j * columns + i]`. the grid keeps one control point of margin around
+/// the calibrated pixel area `min..max`, so it needs at least 4 x 4 control points.
+/// deserialization checks the grid like `BSplineCamera::new`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "BSplineGrid")]
+pub struct BSplineCamera {
+    pub min: (f64, f64),
+    pub max: (f64, f64),
+    pub columns: usize,
+    pub rows: usize,
+    pub grid: Vec<[f64; 3]>,
+}
+
+/// the unchecked fields of a `BSplineCamera`
+#[derive(Deserialize)]
+struct BSplineGrid {
+    min: (f64, f64),
+    max: (f64, f64),
+    columns: usize,
+    rows: usize,
+    grid: Vec<[f64; 3]>,
+}
+impl TryFrom<BSplineGrid> for BSplineCamera {
+    type Error = BSplineError;
+    fn try_from(grid: BSplineGrid) -> Result<Self, Self::Error> {
+        BSplineCamera::new(grid.min, grid.max, grid.columns, grid.rows, grid.grid)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum BSplineError {
+    /// less than 4 x 4 control points
+    TooFewControlPoints { columns: usize, rows: usize },
+    /// the grid does not have `columns * rows` control points
+    GridSize { expected: usize, found: usize },
+    /// the calibrated area `min..max` is empty or not finite
+    EmptyArea,
+    /// the fitted projection has no finite direction at the sample pixel `(u, v)`
+    InvalidSample { u: f64, v: f64 },
+    /// the samples of the fit do not determine every control point
+    Unconstrained,
+}
+impl fmt::Display for BSplineError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BSplineError::TooFewControlPoints { columns, rows } => write!(
+                f,
+                "the grid needs at least 4 x 4 control points, found {} x {}",
+                columns, rows
+            ),
+            BSplineError::GridSize { expected, found } => {
+                write!(f, "expected {} control points, found {}", expected, found)
+            }
+            BSplineError::EmptyArea => write!(f, "the calibrated area is empty"),
+            BSplineError::InvalidSample { u, v } => {
+                write!(f, "the projection has no direction at ({}, {})", u, v)
+            }
+            BSplineError::Unconstrained => {
+                write!(f, "the samples do not determine every control point")
+            }
+        }
+    }
+}
+impl std::error::Error for BSplineError {}
+
+/// any of the projections, selected at runtime by the `type` field of a config, e.g.
+/// `{type = "Pinhole", parameters = {fx = 400.0, fy = 400.0, cx = 320.0, cy = 240.0, skew = 0.0}}`
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(tag = "type", content = "parameters")]
+pub enum AnyProjection {
+    Pinhole(Pinhole),
+    Fisheye(Fisheye),
+    Stereographic(Stereographic),
+    Equisolid(Equisolid),
+    Orthographic(Orthographic),
+    UnifiedCamera(UnifiedCamera),
+    ExtendedUnifiedCamera(ExtendedUnifiedCamera),
+    DoubleSphere(DoubleSphere),
+    Scaramuzza(Scaramuzza),
+    BSplineCamera(BSplineCamera),
+}
+
+impl<T: RealField + Copy> Pinhole<T> {
+    pub fn new(fx: T, fy: T, cx: T, cy: T, skew: T) -> Self {
         Self {
             fx,
             fy,
@@ -32,6 +207,12 @@ impl Pinhole {
             skew,
         }
     }
+    pub fn matrix(self) -> Matrix2x3<T> {
+        Matrix2x3::new(self.fx, self.skew, self.cx, T::zero(), self.fy, self.cy)
+    }
+}
+
+impl Pinhole {
     pub fn from_resolution_fov(resolution: (u32, u32), fov: (f64, f64)) -> Self {
         let (width, height) = resolution;
         let (fov_x, fov_y) = fov;
@@ -41,18 +222,155 @@ impl Pinhole {
         let cy = height as f64 / 2.0;
         Self::new(fx, fy, cx, cy, 0.0)
     }
-    pub fn matrix(self) -> Matrix2x3<f64> {
-        Matrix2x3::new(self.fx, self.skew, self.cx, 0.0, self.fy, self.cy)
+}
+
+/// the projection between rays and pixels, `T` is the scalar type
+pub trait CameraProjection<T = f64> {
+    fn project(&self, rhs: &CameraRay<T>) -> PixelIndex<T>;
+    fn unproject(&self, rhs: &PixelIndex<T>) -> CameraRay<T>;
+    /// whether the ray is in the domain of the projection, `project` returns garbage for the others
+    fn is_valid_ray(&self, ray: &CameraRay<T>) -> bool;
+    /// whether the pixel is the image of a ray, `unproject` returns garbage for the others
+    fn is_valid_pixel(&self, _pixel: &PixelIndex<T>) -> bool {
+        true
+    }
+    /// projects the ray, `None` if it is outside of the domain of the projection
+    fn try_project(&self, ray: &CameraRay<T>) -> Option<PixelIndex<T>> {
+        self.is_valid_ray(ray).then(|| self.project(ray))
     }
 }
 
-pub trait CameraProjection {
-    fn project(&self, rhs: &CameraRay) -> PixelIndex<f64>;
-    fn unproject(&self, rhs: &PixelIndex<f64>) -> CameraRay;
+/// whether the angle of the ray to the optical axis is below 180°, the backwards axis has no azimuth
+fn is_off_rear_axis<T: RealField + Copy>(ray: &CameraRay<T>) -> bool {
+    ray.vector.z > T::zero() || !ray.vector.x.is_zero() || !ray.vector.y.is_zero()
 }
 
-impl CameraProjection for Pinhole {
-    fn project(&self, ray: &CameraRay) -> PixelIndex<f64> {
+/// the projections which end in the affine map `u = fx x + skew y + cx`, `v = fy y + cy`, resampling the image
+/// only changes these intrinsics and keeps a distortion in front of the projection valid
+///
+/// the pixel centers are at integer coordinates, i.e. the image covers `-0.5` to `width - 0.5`
+pub trait AffineIntrinsics: Sized {
+    /// the projection followed by `u' = sx u + tx` and `v' = sy v + ty`
+    fn map_pixels(&self, x: (f64, f64), y: (f64, f64)) -> Self;
+    /// resizing the image by `sx` and `sy`, the image borders stay aligned like for `cv::resize`
+    ///
+    /// # Panics
+    /// if a factor is not positive and finite
+    fn scale(&self, sx: f64, sy: f64) -> Self {
+        let valid = |factor: f64| factor > 0.0 && factor.is_finite();
+        assert!(valid(sx) && valid(sy), "invalid scale {} x {}", sx, sy);
+        self.map_pixels((sx, 0.5 * sx - 0.5), (sy, 0.5 * sy - 0.5))
+    }
+    /// cutting out the region of interest `(x, y, width, height)`, only the offset is relevant
+    fn crop(&self, (x, y, _, _): (u32, u32, u32, u32)) -> Self {
+        self.map_pixels((1.0, -(x as f64)), (1.0, -(y as f64)))
+    }
+    /// combining `factor` x `factor` pixels into one, the new pixel centers are the centers of the blocks
+    ///
+    /// # Panics
+    /// if `factor` is zero
+    fn bin(&self, factor: u32) -> Self {
+        assert!(factor > 0, "the binning factor must be positive");
+        let scale = 1.0 / factor as f64;
+        self.scale(scale, scale)
+    }
+    /// the level `n` of a `cv::pyrDown` pyramid, every level keeps the pixels with even coordinates
+    fn pyramid_level(&self, n: u32) -> Self {
+        let scale = 0.5f64.powi(n as i32);
+        self.map_pixels((scale, 0.0), (scale, 0.0))
+    }
+    /// the intrinsics for the rotated or flipped image of an image with `resolution`, the camera frame turns
+    /// along, see `ImageTransform`. quarter turns move the skew below the diagonal, so they give `None` for
+    /// skewed intrinsics
+    fn transform_image(&self, transform: ImageTransform, resolution: (u32, u32)) -> Option<Self>;
+}
+
+/// the rotations by multiples of 90° of `cv::rotate` and the mirroring of `cv::flip`
+///
+/// the camera frame turns along with the image, the ray `(x, y, z)` becomes `(x', y', z) = M (x, y), z` for the
+/// signed permutation `M` of `matrix`, which maps the pixels as `p' = M p + offset`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageTransform {
+    Rotate90Clockwise,
+    Rotate180,
+    Rotate90Counterclockwise,
+    /// mirroring at the vertical axis, i.e. left and right swap
+    FlipHorizontal,
+    /// mirroring at the horizontal axis, i.e. top and bottom swap
+    FlipVertical,
+}
+
+impl ImageTransform {
+    pub fn matrix(self) -> Matrix2<f64> {
+        match self {
+            ImageTransform::Rotate90Clockwise => Matrix2::new(0.0, -1.0, 1.0, 0.0),
+            ImageTransform::Rotate180 => Matrix2::new(-1.0, 0.0, 0.0, -1.0),
+            ImageTransform::Rotate90Counterclockwise => Matrix2::new(0.0, 1.0, -1.0, 0.0),
+            ImageTransform::FlipHorizontal => Matrix2::new(-1.0, 0.0, 0.0, 1.0),
+            ImageTransform::FlipVertical => Matrix2::new(1.0, 0.0, 0.0, -1.0),
+        }
+    }
+    /// the shift which keeps the pixel centers, which are at integer coordinates, inside of the image
+    pub fn offset(self, (width, height): (u32, u32)) -> Vector2<f64> {
+        let (right, bottom) = (width as f64 - 1.0, height as f64 - 1.0);
+        match self {
+            ImageTransform::Rotate90Clockwise => Vector2::new(bottom, 0.0),
+            ImageTransform::Rotate180 => Vector2::new(right, bottom),
+            ImageTransform::Rotate90Counterclockwise => Vector2::new(0.0, right),
+            ImageTransform::FlipHorizontal => Vector2::new(right, 0.0),
+            ImageTransform::FlipVertical => Vector2::new(0.0, bottom),
+        }
+    }
+    pub fn inverse(self) -> Self {
+        match self {
+            ImageTransform::Rotate90Clockwise => ImageTransform::Rotate90Counterclockwise,
+            ImageTransform::Rotate90Counterclockwise => ImageTransform::Rotate90Clockwise,
+            transform => transform,
+        }
+    }
+    /// the resolution of the transformed image
+    pub fn resolution(self, (width, height): (u32, u32)) -> (u32, u32) {
+        match self {
+            ImageTransform::Rotate90Clockwise | ImageTransform::Rotate90Counterclockwise => {
+                (height, width)
+            }
+            _ => (width, height),
+        }
+    }
+    /// where the pixel of an image with `resolution` ends up in the transformed image
+    pub fn pixel(
+        self,
+        PixelIndex(u, v): &PixelIndex<f64>,
+        resolution: (u32, u32),
+    ) -> PixelIndex<f64> {
+        let pixel = self.matrix() * Vector2::new(*u, *v) + self.offset(resolution);
+        PixelIndex(pixel.x, pixel.y)
+    }
+    /// the ray in the transformed camera frame
+    pub fn ray(self, ray: &CameraRay) -> CameraRay {
+        let xy = self.matrix() * ray.vector.xy();
+        CameraRay::new(xy.x, xy.y, ray.vector.z)
+    }
+}
+
+/// the intrinsics `(fx, fy, cx, cy, skew)` of the transformed image, the affine part `A` becomes `M A M^T`
+fn transform_intrinsics(
+    (fx, fy, cx, cy, skew): (f64, f64, f64, f64, f64),
+    transform: ImageTransform,
+    resolution: (u32, u32),
+) -> Option<(f64, f64, f64, f64, f64)> {
+    let m = transform.matrix();
+    let a = m * Matrix2::new(fx, skew, 0.0, fy) * m.transpose();
+    if a[(1, 0)] != 0.0 {
+        return None;
+    }
+    let c = m * Vector2::new(cx, cy) + transform.offset(resolution);
+    Some((a[(0, 0)], a[(1, 1)], c.x, c.y, a[(0, 1)]))
+}
+
+impl<T: RealField + Copy> CameraProjection<T> for Pinhole<T> {
+    /// rays are scaled onto the image plane at `z = 1` first
+    fn project(&self, ray: &CameraRay<T>) -> PixelIndex<T> {
         let Pinhole {
             fx,
             fy,
@@ -60,79 +378,842 @@ impl CameraProjection for Pinhole {
             cy,
             skew,
         } = *self;
-        let (x, y) = ray.xy();
+        let (x, y) = (ray.vector.x / ray.vector.z, ray.vector.y / ray.vector.z);
         let u = fx * x + skew * y + cx;
         let v = fy * y + cy;
         PixelIndex(u, v)
     }
-    fn unproject(&self, PixelIndex(u, v): &PixelIndex<f64>) -> CameraRay {
+    fn unproject(&self, PixelIndex(u, v): &PixelIndex<T>) -> CameraRay<T> {
         let Pinhole {
             fx,
             fy,
             cx,
             cy,
             skew,
-        } = self;
+        } = *self;
 
-        let y = (v - cy) / fy;
-        let x = (u - cx - skew * y) / fx;
-        CameraRay::new(x, y, 1.0)
+        let y = (*v - cy) / fy;
+        let x = (*u - cx - skew * y) / fx;
+        CameraRay::new(x, y, T::one())
+    }
+    /// only rays in front of the camera
+    fn is_valid_ray(&self, ray: &CameraRay<T>) -> bool {
+        ray.vector.z > T::zero()
     }
 }
 
-impl CameraProjection for Fisheye {
-    fn project(&self, ray: &CameraRay) -> PixelIndex<f64> {
+impl<T: RealField + Copy> CameraProjection<T> for Fisheye<T> {
+    fn project(&self, ray: &CameraRay<T>) -> PixelIndex<T> {
         let Fisheye {
             fx,
             fy,
             cx,
             cy,
             skew,
-        } = self;
-        // cross product with a pure z vector is: (-y, x, 0) and its norm is len(ray)*sin(angle)
-        let x = ray.vector.x;
-        let y = ray.vector.y;
-        let len = ray.vector.norm();
-        let axis_sin = Vector3::new(-y, x, 0.0) / len;
-        let angle = axis_sin.norm().asin();
-        if axis_sin.norm() < f64::EPSILON.sqrt() {
-            PixelIndex(*cx, *cy)
-        } else {
-            let axis = Vector3::from_row_slice(&[0., 0., 1.])
-                .cross(&ray.vector)
-                .normalize()
-                * angle;
-            let theta_x = axis.y;
-            let theta_y = -axis.x;
-            let u = fx * theta_x + skew * theta_y + cx;
-            let v = fy * theta_y + cy;
-            PixelIndex(u, v)
+        } = *self;
+        // the angle to the optical axis, atan2 keeps rays behind the camera apart from the ones in front
+        let (x, y, z) = (ray.vector.x, ray.vector.y, ray.vector.z);
+        let r = x.hypot(y);
+        if r < T::default_epsilon().sqrt() {
+            return PixelIndex(cx, cy);
         }
+        let theta = r.atan2(z);
+        let theta_x = theta * x / r;
+        let theta_y = theta * y / r;
+        let u = fx * theta_x + skew * theta_y + cx;
+        let v = fy * theta_y + cy;
+        PixelIndex(u, v)
     }
-    fn unproject(&self, PixelIndex(u, v): &PixelIndex<f64>) -> CameraRay {
+    fn unproject(&self, PixelIndex(u, v): &PixelIndex<T>) -> CameraRay<T> {
         let Fisheye {
             fx,
             fy,
             cx,
             cy,
             skew,
-        } = self;
+        } = *self;
 
-        let theta_y = (v - cy) / fy;
-        let theta_x = (u - cx - skew * theta_y) / fx;
+        let theta_y = (*v - cy) / fy;
+        let theta_x = (*u - cx - skew * theta_y) / fx;
         let ray = Vector3::z_axis();
-        let rot = Rotation3::from_scaled_axis(Vector3::new(-theta_y, theta_x, 0.0));
+        let rot = Rotation3::from_scaled_axis(Vector3::new(-theta_y, theta_x, T::zero()));
         let ray = rot * ray;
         let x = ray.x;
         let y = ray.y;
         let z = ray.z;
         CameraRay::new(x, y, z)
     }
+    /// all rays except the one straight backwards
+    fn is_valid_ray(&self, ray: &CameraRay<T>) -> bool {
+        is_off_rear_axis(ray)
+    }
+}
+
+impl AffineIntrinsics for Pinhole {
+    fn map_pixels(&self, (sx, tx): (f64, f64), (sy, ty): (f64, f64)) -> Self {
+        Self::new(
+            sx * self.fx,
+            sy * self.fy,
+            sx * self.cx + tx,
+            sy * self.cy + ty,
+            sx * self.skew,
+        )
+    }
+    fn transform_image(&self, transform: ImageTransform, resolution: (u32, u32)) -> Option<Self> {
+        let intrinsics = (self.fx, self.fy, self.cx, self.cy, self.skew);
+        let (fx, fy, cx, cy, skew) = transform_intrinsics(intrinsics, transform, resolution)?;
+        Some(Self::new(fx, fy, cx, cy, skew))
+    }
+}
+
+impl AffineIntrinsics for Fisheye {
+    fn map_pixels(&self, (sx, tx): (f64, f64), (sy, ty): (f64, f64)) -> Self {
+        Self {
+            fx: sx * self.fx,
+            fy: sy * self.fy,
+            cx: sx * self.cx + tx,
+            cy: sy * self.cy + ty,
+            skew: sx * self.skew,
+        }
+    }
+    /// the angles to the optical axis turn like the rays, so the pinhole rules apply
+    fn transform_image(&self, transform: ImageTransform, resolution: (u32, u32)) -> Option<Self> {
+        let intrinsics = (self.fx, self.fy, self.cx, self.cy, self.skew);
+        let (fx, fy, cx, cy, skew) = transform_intrinsics(intrinsics, transform, resolution)?;
+        Some(Self {
+            fx,
+            fy,
+            cx,
+            cy,
+            skew,
+        })
+    }
+}
+
+impl UnifiedCamera {
+    pub fn new(fx: f64, fy: f64, cx: f64, cy: f64, xi: f64) -> Self {
+        Self { fx, fy, cx, cy, xi }
+    }
+}
+
+impl CameraProjection for UnifiedCamera {
+    fn project(&self, ray: &CameraRay) -> PixelIndex<f64> {
+        let UnifiedCamera { fx, fy, cx, cy, xi } = *self;
+        let (x, y) = ray.xy();
+        let z = ray.vector.z;
+        let denominator = z + xi * ray.vector.norm();
+        PixelIndex(fx * x / denominator + cx, fy * y / denominator + cy)
+    }
+    fn unproject(&self, PixelIndex(u, v): &PixelIndex<f64>) -> CameraRay {
+        let UnifiedCamera { fx, fy, cx, cy, xi } = *self;
+        let mx = (u - cx) / fx;
+        let my = (v - cy) / fy;
+        let r2 = mx * mx + my * my;
+        let factor = (xi + (1.0 + (1.0 - xi * xi) * r2).sqrt()) / (1.0 + r2);
+        CameraRay::new(factor * mx, factor * my, factor - xi)
+    }
+    /// rays which are hidden behind the sphere, i.e. rays with `z <= -w |ray|`, can not be projected
+    fn is_valid_ray(&self, ray: &CameraRay) -> bool {
+        let w = if self.xi > 1.0 {
+            1.0 / self.xi
+        } else {
+            self.xi
+        };
+        ray.vector.z > -w * ray.vector.norm()
+    }
+    /// for `xi > 1` only pixels inside the image of the sphere's horizon can be unprojected
+    fn is_valid_pixel(&self, PixelIndex(u, v): &PixelIndex<f64>) -> bool {
+        let mx = (u - self.cx) / self.fx;
+        let my = (v - self.cy) / self.fy;
+        1.0 + (1.0 - self.xi * self.xi) * (mx * mx + my * my) >= 0.0
+    }
+}
+
+impl ExtendedUnifiedCamera {
+    pub fn new(fx: f64, fy: f64, cx: f64, cy: f64, alpha: f64, beta: f64) -> Self {
+        Self {
+            fx,
+            fy,
+            cx,
+            cy,
+            alpha,
+            beta,
+        }
+    }
+    fn rho(&self, ray: &CameraRay) -> f64 {
+        let (x, y) = ray.xy();
+        let z = ray.vector.z;
+        (self.beta * (x * x + y * y) + z * z).sqrt()
+    }
+}
+
+impl CameraProjection for ExtendedUnifiedCamera {
+    fn project(&self, ray: &CameraRay) -> PixelIndex<f64> {
+        let ExtendedUnifiedCamera {
+            fx,
+            fy,
+            cx,
+            cy,
+            alpha,
+            ..
+        } = *self;
+        let (x, y) = ray.xy();
+        let denominator = alpha * self.rho(ray) + (1.0 - alpha) * ray.vector.z;
+        PixelIndex(fx * x / denominator + cx, fy * y / denominator + cy)
+    }
+    fn unproject(&self, PixelIndex(u, v): &PixelIndex<f64>) -> CameraRay {
+        let ExtendedUnifiedCamera {
+            fx,
+            fy,
+            cx,
+            cy,
+            alpha,
+            beta,
+        } = *self;
+        let mx = (u - cx) / fx;
+        let my = (v - cy) / fy;
+        let r2 = mx * mx + my * my;
+        let mz = (1.0 - alpha * alpha * beta * r2)
+            / (alpha * (1.0 - (2.0 * alpha - 1.0) * beta * r2).sqrt() + 1.0 - alpha);
+        CameraRay::new(mx, my, mz)
+    }
+    /// rays which are hidden behind the ellipsoid can not be projected
+    fn is_valid_ray(&self, ray: &CameraRay) -> bool {
+        let alpha = self.alpha;
+        let w = if alpha > 0.5 {
+            (1.0 - alpha) / alpha
+        } else {
+            alpha / (1.0 - alpha)
+        };
+        ray.vector.z > -w * self.rho(ray)
+    }
+    /// for `alpha > 0.5` only pixels inside the image of the ellipsoid's horizon can be unprojected
+    fn is_valid_pixel(&self, PixelIndex(u, v): &PixelIndex<f64>) -> bool {
+        let mx = (u - self.cx) / self.fx;
+        let my = (v - self.cy) / self.fy;
+        self.alpha <= 0.5 || (mx * mx + my * my) < 1.0 / ((2.0 * self.alpha - 1.0) * self.beta)
+    }
+}
+
+impl DoubleSphere {
+    pub fn new(fx: f64, fy: f64, cx: f64, cy: f64, xi: f64, alpha: f64) -> Self {
+        Self {
+            fx,
+            fy,
+            cx,
+            cy,
+            xi,
+            alpha,
+        }
+    }
+}
+
+impl CameraProjection for DoubleSphere {
+    fn project(&self, ray: &CameraRay) -> PixelIndex<f64> {
+        let DoubleSphere {
+            fx,
+            fy,
+            cx,
+            cy,
+            xi,
+            alpha,
+        } = *self;
+        let (x, y) = ray.xy();
+        let z = xi * ray.vector.norm() + ray.vector.z;
+        let d2 = (x * x + y * y + z * z).sqrt();
+        let denominator = alpha * d2 + (1.0 - alpha) * z;
+        PixelIndex(fx * x / denominator + cx, fy * y / denominator + cy)
+    }
+    fn unproject(&self, PixelIndex(u, v): &PixelIndex<f64>) -> CameraRay {
+        let DoubleSphere {
+            fx,
+            fy,
+            cx,
+            cy,
+            xi,
+            alpha,
+        } = *self;
+        let mx = (u - cx) / fx;
+        let my = (v - cy) / fy;
+        let r2 = mx * mx + my * my;
+        let mz = (1.0 - alpha * alpha * r2)
+            / (alpha * (1.0 - (2.0 * alpha - 1.0) * r2).sqrt() + 1.0 - alpha);
+        let factor = (mz * xi + (mz * mz + (1.0 - xi * xi) * r2).sqrt()) / (mz * mz + r2);
+        CameraRay::new(factor * mx, factor * my, factor * mz - xi)
+    }
+    /// rays with `z <= -w2 |ray|` can not be projected
+    fn is_valid_ray(&self, ray: &CameraRay) -> bool {
+        let DoubleSphere { xi, alpha, .. } = *self;
+        let w1 = if alpha <= 0.5 {
+            alpha / (1.0 - alpha)
+        } else {
+            (1.0 - alpha) / alpha
+        };
+        let w2 = (w1 + xi) / (2.0 * w1 * xi + xi * xi + 1.0).sqrt();
+        ray.vector.z > -w2 * ray.vector.norm()
+    }
+    /// for `alpha > 0.5` only pixels inside the image of the horizon can be unprojected
+    fn is_valid_pixel(&self, PixelIndex(u, v): &PixelIndex<f64>) -> bool {
+        let mx = (u - self.cx) / self.fx;
+        let my = (v - self.cy) / self.fy;
+        self.alpha <= 0.5 || (mx * mx + my * my) <= 1.0 / (2.0 * self.alpha - 1.0)
+    }
+}
+
+impl Scaramuzza {
+    pub(crate) fn evaluate(coefficients: &[f64], x: f64) -> f64 {
+        coefficients.iter().rev().fold(0.0, |acc, c| acc * x + c)
+    }
+    /// replaces the inverse polynomial by a least squares fit of `polynomial` for radii up to `max_radius` pixels,
+    /// like `findinvpoly` of OCamCalib
+    pub fn fit_inverse_polynomial(&mut self, degree: usize, max_radius: f64) {
+        const SAMPLES: usize = 1000;
+        let samples: Vec<(f64, f64)> = (0..=SAMPLES)
+            .map(|i| {
+                let r = max_radius * i as f64 / SAMPLES as f64;
+                (Self::evaluate(&self.polynomial, r).atan2(r), r)
+            })
+            .collect();
+        let a = DMatrix::from_fn(samples.len(), degree + 1, |i, j| {
+            samples[i].0.powi(j as i32)
+        });
+        let b = DVector::from_iterator(samples.len(), samples.iter().map(|(_, r)| *r));
+        let solution = a
+            .svd(true, true)
+            .solve(&b, f64::EPSILON)
+            .expect("svd was computed with u and v");
+        self.inverse_polynomial = solution.iter().copied().collect();
+    }
+}
+
+impl CameraProjection for Scaramuzza {
+    fn project(&self, ray: &CameraRay) -> PixelIndex<f64> {
+        // into the OCamCalib frame
+        let (x, y, z) = (ray.vector.y, ray.vector.x, -ray.vector.z);
+        let norm = x.hypot(y);
+        if norm < f64::EPSILON {
+            return PixelIndex(self.cx, self.cy);
+        }
+        let theta = z.atan2(norm);
+        let rho = Self::evaluate(&self.inverse_polynomial, theta);
+        let x = x / norm * rho;
+        let y = y / norm * rho;
+        let row = x * self.c + y * self.d + self.cy;
+        let col = x * self.e + y + self.cx;
+        PixelIndex(col, row)
+    }
+    fn unproject(&self, PixelIndex(u, v): &PixelIndex<f64>) -> CameraRay {
+        let Scaramuzza { c, d, e, .. } = *self;
+        let row = v - self.cy;
+        let col = u - self.cx;
+        let inv_det = 1.0 / (c - d * e);
+        let x = inv_det * (row - d * col);
+        let y = inv_det * (-e * row + c * col);
+        let z = Self::evaluate(&self.polynomial, x.hypot(y));
+        // back from the OCamCalib frame
+        CameraRay {
+            vector: Vector3::new(y, x, -z).normalize(),
+        }
+    }
+    /// all rays except the one straight backwards, the accuracy depends on the range of the inverse polynomial
+    fn is_valid_ray(&self, ray: &CameraRay) -> bool {
+        is_off_rear_axis(ray)
+    }
+}
+
+impl BSplineCamera {
+    /// checks that the grid has `columns * rows` control points and covers a non empty area
+    pub fn new(
+        min: (f64, f64),
+        max: (f64, f64),
+        columns: usize,
+        rows: usize,
+        grid: Vec<[f64; 3]>,
+    ) -> Result<Self, BSplineError> {
+        if columns < 4 || rows < 4 {
+            return Err(BSplineError::TooFewControlPoints { columns, rows });
+        }
+        if grid.len() != columns * rows {
+            return Err(BSplineError::GridSize {
+                expected: columns * rows,
+                found: grid.len(),
+            });
+        }
+        let extent = |size: f64| size > 0.0 && size.is_finite();
+        if !(extent(max.0 - min.0) && extent(max.1 - min.1)) {
+            return Err(BSplineError::EmptyArea);
+        }
+        Ok(Self {
+            min,
+            max,
+            columns,
+            rows,
+            grid,
+        })
+    }
+    /// fits the grid to the directions of `projection` on the pixel area `min..max` by least squares,
+    /// fails for the grids `new` rejects and if `projection` has no direction for a sample pixel
+    pub fn fit<P: CameraProjection>(
+        projection: &P,
+        min: (f64, f64),
+        max: (f64, f64),
+        columns: usize,
+        rows: usize,
+    ) -> Result<Self, BSplineError> {
+        let mut camera = Self::new(min, max, columns, rows, vec![[0.0; 3]; columns * rows])?;
+        // three samples per grid cell and direction
+        let (nu, nv) = (3 * (columns - 3) + 1, 3 * (rows - 3) + 1);
+        let mut a = DMatrix::zeros(nu * nv, columns * rows);
+        let mut b = DMatrix::zeros(nu * nv, 3);
+        for j in 0..nv {
+            for i in 0..nu {
+                let sample = j * nu + i;
+                let u = min.0 + (max.0 - min.0) * i as f64 / (nu - 1) as f64;
+                let v = min.1 + (max.1 - min.1) * j as f64 / (nv - 1) as f64;
+                for (index, weight, _, _) in camera.weights(&PixelIndex(u, v)) {
+                    a[(sample, index)] = weight;
+                }
+                let direction = projection.unproject(&PixelIndex(u, v)).vector.normalize();
+                if !direction.iter().all(|x| x.is_finite()) {
+                    return Err(BSplineError::InvalidSample { u, v });
+                }
+                b.row_mut(sample).copy_from(&direction.transpose());
+            }
+        }
+        let solution = a
+            .tr_mul(&a)
+            .cholesky()
+            .ok_or(BSplineError::Unconstrained)?
+            .solve(&a.tr_mul(&b));
+        for (index, point) in camera.grid.iter_mut().enumerate() {
+            *point = [
+                solution[(index, 0)],
+                solution[(index, 1)],
+                solution[(index, 2)],
+            ];
+        }
+        Ok(camera)
+    }
+    /// the scale from pixels to grid cells
+    fn scale(&self) -> (f64, f64) {
+        (
+            (self.columns - 3) as f64 / (self.max.0 - self.min.0),
+            (self.rows - 3) as f64 / (self.max.1 - self.min.1),
+        )
+    }
+    /// the 16 control points with support at `pixel` as `(index, weight, d/du, d/dv)`,
+    /// pixels outside of the calibrated area extrapolate the border cells
+    pub(crate) fn weights(
+        &self,
+        PixelIndex(u, v): &PixelIndex<f64>,
+    ) -> Vec<(usize, f64, f64, f64)> {
+        let (scale_x, scale_y) = self.scale();
+        let (i, tx) = segment(1.0 + (u - self.min.0) * scale_x, self.columns);
+        let (j, ty) = segment(1.0 + (v - self.min.1) * scale_y, self.rows);
+        let (bx, dbx) = cubic_basis(tx);
+        let (by, dby) = cubic_basis(ty);
+        let mut weights = Vec::with_capacity(16);
+        for y in 0..4 {
+            for x in 0..4 {
+                weights.push((
+                    (j + y - 1) * self.columns + i + x - 1,
+                    bx[x] * by[y],
+                    dbx[x] * by[y] * scale_x,
+                    bx[x] * dby[y] * scale_y,
+                ));
+            }
+        }
+        weights
+    }
+    /// the spline value at `pixel` and its derivatives along u and v
+    pub(crate) fn evaluate(
+        &self,
+        pixel: &PixelIndex<f64>,
+    ) -> (Vector3<f64>, Vector3<f64>, Vector3<f64>) {
+        self.weights(pixel).into_iter().fold(
+            (Vector3::zeros(), Vector3::zeros(), Vector3::zeros()),
+            |(value, du, dv), (index, weight, wu, wv)| {
+                let point = Vector3::from(self.grid[index]);
+                (value + point * weight, du + point * wu, dv + point * wv)
+            },
+        )
+    }
+    /// projects with the given solver, fails if gauss newton does not converge or if it is stopped by the
+    /// border of the calibrated area before reaching the ray
+    pub fn project_with(
+        &self,
+        ray: &CameraRay,
+        solver: &IterativeSolver,
+    ) -> Result<PixelIndex<f64>, DistortionError> {
+        let (pixel, residual, iterations) = self.solve(ray, solver);
+        let PixelIndex(u, v) = pixel;
+        let clamped = u == self.min.0 || u == self.max.0 || v == self.min.1 || v == self.max.1;
+        match iterations {
+            Some(_) if clamped && residual > solver.tolerance => Err(DistortionError::OutOfDomain),
+            Some(_) => Ok(pixel),
+            None => Err(DistortionError::NotConverged {
+                iterations: solver.max_iterations,
+                residual,
+            }),
+        }
+    }
+    /// gauss newton on the normalized direction, clamped to the calibrated area. returns the last pixel, the
+    /// remaining distance between the imaged direction and the ray and the iterations if it converged
+    fn solve(
+        &self,
+        ray: &CameraRay,
+        solver: &IterativeSolver,
+    ) -> (PixelIndex<f64>, f64, Option<usize>) {
+        let target = ray.vector.normalize();
+        // start at the control point looking closest to the ray
+        let (mut i, mut j) = (1, 1);
+        let mut best = f64::NEG_INFINITY;
+        for y in 1..self.rows - 1 {
+            for x in 1..self.columns - 1 {
+                let dot = Vector3::from(self.grid[y * self.columns + x])
+                    .normalize()
+                    .dot(&target);
+                if dot > best {
+                    (i, j, best) = (x, y, dot);
+                }
+            }
+        }
+        let (scale_x, scale_y) = self.scale();
+        let mut pixel = PixelIndex(
+            (self.min.0 + (i as f64 - 1.0) / scale_x).clamp(self.min.0, self.max.0),
+            (self.min.1 + (j as f64 - 1.0) / scale_y).clamp(self.min.1, self.max.1),
+        );
+        let residual = |pixel: &PixelIndex<f64>| (self.unproject(pixel).vector - target).norm();
+        for iteration in 0..solver.max_iterations {
+            let (value, du, dv) = self.evaluate(&pixel);
+            let norm = value.norm();
+            let direction = value / norm;
+            let projector = (Matrix3::identity() - direction * direction.transpose()) / norm;
+            let jacobian = Matrix3x2::from_columns(&[projector * du, projector * dv]);
+            let Some(step) = (jacobian.transpose() * jacobian)
+                .try_inverse()
+                .map(|inverse| -inverse * jacobian.transpose() * (direction - target))
+            else {
+                break;
+            };
+            let next = PixelIndex(
+                (pixel.0 + step.x).clamp(self.min.0, self.max.0),
+                (pixel.1 + step.y).clamp(self.min.1, self.max.1),
+            );
+            // a step which is cut off by the clamp has converged as well
+            let moved = (next.0 - pixel.0).hypot(next.1 - pixel.1);
+            pixel = next;
+            if moved < solver.tolerance {
+                let residual = residual(&pixel);
+                return (pixel, residual, Some(iteration + 1));
+            }
+        }
+        let residual = residual(&pixel);
+        (pixel, residual, None)
+    }
+}
+
+impl CameraProjection for BSplineCamera {
+    /// the pixel of the calibrated area which images the closest direction, even if the solver did not
+    /// converge. `project_with` reports the convergence
+    fn project(&self, ray: &CameraRay) -> PixelIndex<f64> {
+        self.solve(ray, &IterativeSolver::default()).0
+    }
+    fn unproject(&self, pixel: &PixelIndex<f64>) -> CameraRay {
+        CameraRay {
+            vector: self.evaluate(pixel).0.normalize(),
+        }
+    }
+    /// the rays which are imaged inside of the calibrated area. this runs the full gauss newton solve,
+    /// `try_project` checks the ray and projects it with a single solve
+    fn is_valid_ray(&self, ray: &CameraRay) -> bool {
+        self.try_project(ray).is_some()
+    }
+    /// only the calibrated area, outside of it the border cells are extrapolated
+    fn is_valid_pixel(&self, PixelIndex(u, v): &PixelIndex<f64>) -> bool {
+        (self.min.0..=self.max.0).contains(u) && (self.min.1..=self.max.1).contains(v)
+    }
+    /// `None` for the rays outside of the calibrated area and if the solver did not converge
+    fn try_project(&self, ray: &CameraRay) -> Option<PixelIndex<f64>> {
+        self.project_with(ray, &IterativeSolver::default()).ok()
+    }
+}
+
+impl CameraProjection for AnyProjection {
+    fn project(&self, ray: &CameraRay) -> PixelIndex<f64> {
+        match self {
+            AnyProjection::Pinhole(projection) => projection.project(ray),
+            AnyProjection::Fisheye(projection) => projection.project(ray),
+            AnyProjection::Stereographic(projection) => projection.project(ray),
+            AnyProjection::Equisolid(projection) => projection.project(ray),
+            AnyProjection::Orthographic(projection) => projection.project(ray),
+            AnyProjection::UnifiedCamera(projection) => projection.project(ray),
+            AnyProjection::ExtendedUnifiedCamera(projection) => projection.project(ray),
+            AnyProjection::DoubleSphere(projection) => projection.project(ray),
+            AnyProjection::Scaramuzza(projection) => projection.project(ray),
+            AnyProjection::BSplineCamera(projection) => projection.project(ray),
+        }
+    }
+    fn unproject(&self, pixel: &PixelIndex<f64>) -> CameraRay {
+        match self {
+            AnyProjection::Pinhole(projection) => projection.unproject(pixel),
+            AnyProjection::Fisheye(projection) => projection.unproject(pixel),
+            AnyProjection::Stereographic(projection) => projection.unproject(pixel),
+            AnyProjection::Equisolid(projection) => projection.unproject(pixel),
+            AnyProjection::Orthographic(projection) => projection.unproject(pixel),
+            AnyProjection::UnifiedCamera(projection) => projection.unproject(pixel),
+            AnyProjection::ExtendedUnifiedCamera(projection) => projection.unproject(pixel),
+            AnyProjection::DoubleSphere(projection) => projection.unproject(pixel),
+            AnyProjection::Scaramuzza(projection) => projection.unproject(pixel),
+            AnyProjection::BSplineCamera(projection) => projection.unproject(pixel),
+        }
+    }
+    fn is_valid_ray(&self, ray: &CameraRay) -> bool {
+        match self {
+            AnyProjection::Pinhole(projection) => projection.is_valid_ray(ray),
+            AnyProjection::Fisheye(projection) => projection.is_valid_ray(ray),
+            AnyProjection::Stereographic(projection) => projection.is_valid_ray(ray),
+            AnyProjection::Equisolid(projection) => projection.is_valid_ray(ray),
+            AnyProjection::Orthographic(projection) => projection.is_valid_ray(ray),
+            AnyProjection::UnifiedCamera(projection) => projection.is_valid_ray(ray),
+            AnyProjection::ExtendedUnifiedCamera(projection) => projection.is_valid_ray(ray),
+            AnyProjection::DoubleSphere(projection) => projection.is_valid_ray(ray),
+            AnyProjection::Scaramuzza(projection) => projection.is_valid_ray(ray),
+            AnyProjection::BSplineCamera(projection) => projection.is_valid_ray(ray),
+        }
+    }
+    fn is_valid_pixel(&self, pixel: &PixelIndex<f64>) -> bool {
+        match self {
+            AnyProjection::Pinhole(projection) => projection.is_valid_pixel(pixel),
+            AnyProjection::Fisheye(projection) => projection.is_valid_pixel(pixel),
+            AnyProjection::Stereographic(projection) => projection.is_valid_pixel(pixel),
+            AnyProjection::Equisolid(projection) => projection.is_valid_pixel(pixel),
+            AnyProjection::Orthographic(projection) => projection.is_valid_pixel(pixel),
+            AnyProjection::UnifiedCamera(projection) => projection.is_valid_pixel(pixel),
+            AnyProjection::ExtendedUnifiedCamera(projection) => projection.is_valid_pixel(pixel),
+            AnyProjection::DoubleSphere(projection) => projection.is_valid_pixel(pixel),
+            AnyProjection::Scaramuzza(projection) => projection.is_valid_pixel(pixel),
+            AnyProjection::BSplineCamera(projection) => projection.is_valid_pixel(pixel),
+        }
+    }
+    fn try_project(&self, ray: &CameraRay) -> Option<PixelIndex<f64>> {
+        match self {
+            AnyProjection::Pinhole(projection) => projection.try_project(ray),
+            AnyProjection::Fisheye(projection) => projection.try_project(ray),
+            AnyProjection::Stereographic(projection) => projection.try_project(ray),
+            AnyProjection::Equisolid(projection) => projection.try_project(ray),
+            AnyProjection::Orthographic(projection) => projection.try_project(ray),
+            AnyProjection::UnifiedCamera(projection) => projection.try_project(ray),
+            AnyProjection::ExtendedUnifiedCamera(projection) => projection.try_project(ray),
+            AnyProjection::DoubleSphere(projection) => projection.try_project(ray),
+            AnyProjection::Scaramuzza(projection) => projection.try_project(ray),
+            AnyProjection::BSplineCamera(projection) => projection.try_project(ray),
+        }
+    }
+}
+
+/// returns the first control point of the segment containing the grid coordinate `g` and the offset into it
+fn segment(g: f64, points: usize) -> (usize, f64) {
+    let i = (g.floor().max(1.0) as usize).min(points - 3);
+    (i, g - i as f64)
+}
+
+/// the uniform cubic B-spline basis at `t` and its derivatives
+fn cubic_basis(t: f64) -> ([f64; 4], [f64; 4]) {
+    let s = 1.0 - t;
+    let t2 = t * t;
+    let t3 = t2 * t;
+    (
+        [
+            s * s * s / 6.0,
+            (3.0 * t3 - 6.0 * t2 + 4.0) / 6.0,
+            (-3.0 * t3 + 3.0 * t2 + 3.0 * t + 1.0) / 6.0,
+            t3 / 6.0,
+        ],
+        [
+            -s * s / 2.0,
+            (3.0 * t2 - 4.0 * t) / 2.0,
+            (-3.0 * t2 + 2.0 * t + 1.0) / 2.0,
+            t2 / 2.0,
+        ],
+    )
+}
+
+/// returns the angle of the ray to the optical axis and the direction of the ray in the image plane
+fn polar(ray: &CameraRay) -> (f64, f64, f64) {
+    let (x, y) = ray.xy();
+    let r = x.hypot(y);
+    if r < f64::EPSILON {
+        (0.0, 1.0, 0.0)
+    } else {
+        (r.atan2(ray.vector.z), x / r, y / r)
+    }
+}
+
+/// returns the ray with the given angle to the optical axis, the direction is given by `x` and `y`
+fn from_polar(theta: f64, x: f64, y: f64) -> CameraRay {
+    let r = x.hypot(y);
+    if r < f64::EPSILON {
+        CameraRay::new(0.0, 0.0, 1.0)
+    } else {
+        let (sin, cos) = theta.sin_cos();
+        CameraRay::new(sin * x / r, sin * y / r, cos)
+    }
+}
+
+impl Stereographic {
+    pub fn new(fx: f64, fy: f64, cx: f64, cy: f64, skew: f64) -> Self {
+        Self {
+            fx,
+            fy,
+            cx,
+            cy,
+            skew,
+        }
+    }
+}
+
+impl CameraProjection for Stereographic {
+    fn project(&self, ray: &CameraRay) -> PixelIndex<f64> {
+        let Stereographic {
+            fx,
+            fy,
+            cx,
+            cy,
+            skew,
+        } = *self;
+        let (theta, cos_phi, sin_phi) = polar(ray);
+        let r = 2.0 * (theta / 2.0).tan();
+        let x = r * cos_phi;
+        let y = r * sin_phi;
+        PixelIndex(fx * x + skew * y + cx, fy * y + cy)
+    }
+    fn unproject(&self, PixelIndex(u, v): &PixelIndex<f64>) -> CameraRay {
+        let Stereographic {
+            fx,
+            fy,
+            cx,
+            cy,
+            skew,
+        } = self;
+        let y = (v - cy) / fy;
+        let x = (u - cx - skew * y) / fx;
+        let r = x.hypot(y);
+        from_polar(2.0 * (r / 2.0).atan(), x, y)
+    }
+    /// all rays except the one straight backwards, which is imaged at infinity
+    fn is_valid_ray(&self, ray: &CameraRay) -> bool {
+        is_off_rear_axis(ray)
+    }
+}
+
+impl Equisolid {
+    pub fn new(fx: f64, fy: f64, cx: f64, cy: f64, skew: f64) -> Self {
+        Self {
+            fx,
+            fy,
+            cx,
+            cy,
+            skew,
+        }
+    }
+}
+
+impl CameraProjection for Equisolid {
+    fn project(&self, ray: &CameraRay) -> PixelIndex<f64> {
+        let Equisolid {
+            fx,
+            fy,
+            cx,
+            cy,
+            skew,
+        } = *self;
+        let (theta, cos_phi, sin_phi) = polar(ray);
+        let r = 2.0 * (theta / 2.0).sin();
+        let x = r * cos_phi;
+        let y = r * sin_phi;
+        PixelIndex(fx * x + skew * y + cx, fy * y + cy)
+    }
+    fn unproject(&self, PixelIndex(u, v): &PixelIndex<f64>) -> CameraRay {
+        let Equisolid {
+            fx,
+            fy,
+            cx,
+            cy,
+            skew,
+        } = self;
+        let y = (v - cy) / fy;
+        let x = (u - cx - skew * y) / fx;
+        let r = x.hypot(y);
+        from_polar(2.0 * (r / 2.0).asin(), x, y)
+    }
+    /// all rays except the one straight backwards
+    fn is_valid_ray(&self, ray: &CameraRay) -> bool {
+        is_off_rear_axis(ray)
+    }
+    /// the image of the full sphere is the disc with radius 2
+    fn is_valid_pixel(&self, PixelIndex(u, v): &PixelIndex<f64>) -> bool {
+        let y = (v - self.cy) / self.fy;
+        let x = (u - self.cx - self.skew * y) / self.fx;
+        x.hypot(y) <= 2.0
+    }
+}
+
+impl Orthographic {
+    pub fn new(fx: f64, fy: f64, cx: f64, cy: f64, skew: f64) -> Self {
+        Self {
+            fx,
+            fy,
+            cx,
+            cy,
+            skew,
+        }
+    }
+}
+
+impl CameraProjection for Orthographic {
+    fn project(&self, ray: &CameraRay) -> PixelIndex<f64> {
+        let Orthographic {
+            fx,
+            fy,
+            cx,
+            cy,
+            skew,
+        } = *self;
+        let (theta, cos_phi, sin_phi) = polar(ray);
+        let r = theta.sin();
+        let x = r * cos_phi;
+        let y = r * sin_phi;
+        PixelIndex(fx * x + skew * y + cx, fy * y + cy)
+    }
+    fn unproject(&self, PixelIndex(u, v): &PixelIndex<f64>) -> CameraRay {
+        let Orthographic {
+            fx,
+            fy,
+            cx,
+            cy,
+            skew,
+        } = self;
+        let y = (v - cy) / fy;
+        let x = (u - cx - skew * y) / fx;
+        let r = x.hypot(y);
+        from_polar(r.asin(), x, y)
+    }
+    /// only rays up to 90° from the optical axis
+    fn is_valid_ray(&self, ray: &CameraRay) -> bool {
+        ray.vector.z >= 0.0
+    }
+    /// the image of the hemisphere is the unit disc
+    fn is_valid_pixel(&self, PixelIndex(u, v): &PixelIndex<f64>) -> bool {
+        let y = (v - self.cy) / self.fy;
+        let x = (u - self.cx - self.skew * y) / self.fx;
+        x.hypot(y) <= 1.0
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use approx::{AbsDiffEq};
+    use approx::AbsDiffEq;
 
     use super::*;
     const PROJECTION: Fisheye = Fisheye {
@@ -177,4 +1258,422 @@ mod tests {
             assert_eq!(src, dst)
         }
     }
+
+    #[test]
+    fn test_fisheye_behind_image_plane() {
+        // a 235° lens sees up to 117.5° away from the optical axis
+        for (x, y, z) in [
+            (1.0, 0.0, 0.0),
+            (0.3, -0.4, 0.0),
+            (0.5, 0.2, -0.3),
+            (-0.2, -0.6, -0.35),
+        ] {
+            let src = CameraRay::new(x, y, z);
+            let mid = PROJECTION.project(&src);
+            let radius = mid.0.hypot(mid.1);
+            assert!(radius >= std::f64::consts::FRAC_PI_2 - 1e-12, "{:?}", mid);
+            assert_eq!(src, PROJECTION.unproject(&mid));
+            // the mirrored ray in front of the camera lands closer to the center
+            let front = PROJECTION.project(&CameraRay::new(x, y, -z));
+            assert!(z == 0.0 || front.0.hypot(front.1) < radius);
+        }
+    }
+
+    fn assert_pixel2pixel<P: CameraProjection>(projection: &P) {
+        for (u, v) in [(320.0, 240.0), (0.0, 0.0), (400.0, 100.0), (639.0, 479.0)] {
+            let src = PixelIndex(u, v);
+            let ray = projection.unproject(&src);
+            let dst = projection.project(&ray);
+            assert!(src.0.abs_diff_eq(&dst.0, 1e-8) && src.1.abs_diff_eq(&dst.1, 1e-8))
+        }
+    }
+    fn assert_ray2ray<P: CameraProjection>(projection: &P, max_angle: f64) {
+        for (x, y, z) in [
+            (0.0, 0.0, 1.0),
+            (0.2, 0.0, 1.0),
+            (0.0, -0.2, 1.0),
+            (-0.4, 0.3, 1.0),
+            (1.0, 1.0, 0.1),
+            (0.3, -0.3, -0.5),
+        ] {
+            let src = CameraRay::new(x, y, z);
+            if src.vector.angle(&Vector3::z()) > max_angle {
+                continue;
+            }
+            let dst = projection.unproject(&projection.project(&src));
+            assert_eq!(src, dst)
+        }
+    }
+    #[test]
+    fn test_unified() {
+        let projection = UnifiedCamera::new(400.0, 410.0, 320.0, 240.0, 0.9);
+        assert_pixel2pixel(&projection);
+        assert_ray2ray(&projection, 120f64.to_radians());
+        for ((x, y, z), (u, v)) in [
+            ((0.3, -0.2, 1.0), (381.3273343856645, 198.0929881697959)),
+            ((1.0, 0.5, -0.2), (806.4975628397051, 489.33000095534885)),
+        ] {
+            let PixelIndex(pu, pv) = projection.project(&CameraRay::new(x, y, z));
+            assert!(pu.abs_diff_eq(&u, 1e-9) && pv.abs_diff_eq(&v, 1e-9));
+        }
+        assert!(projection.is_valid_ray(&CameraRay::new(1.0, 0.0, -0.8)));
+        assert!(!projection.is_valid_ray(&CameraRay::new(1.0, 0.0, -3.0)));
+        assert!(projection.is_valid_pixel(&PixelIndex(1e5, 1e5)));
+
+        // beyond xi = 1 the image of the sphere is bounded
+        let projection = UnifiedCamera::new(400.0, 400.0, 320.0, 240.0, 2.0);
+        assert!(projection.is_valid_pixel(&PixelIndex(320.0 + 230.0, 240.0)));
+        assert!(!projection.is_valid_pixel(&PixelIndex(320.0 + 232.0, 240.0)));
+        assert!(!projection.is_valid_ray(&CameraRay::new(1.0, 0.0, -0.6)));
+    }
+    #[test]
+    fn test_extended_unified() {
+        let projection = ExtendedUnifiedCamera::new(250.0, 255.0, 320.0, 240.0, 0.6, 1.1);
+        assert_pixel2pixel(&projection);
+        assert_ray2ray(&projection, 120f64.to_radians());
+        for ((x, y, z), (u, v)) in [
+            ((0.3, -0.2, 1.0), (392.0137988677221, 191.030616769949)),
+            ((1.0, 0.5, -0.2), (714.4943514921566, 441.1921192609999)),
+        ] {
+            let PixelIndex(pu, pv) = projection.project(&CameraRay::new(x, y, z));
+            assert!(pu.abs_diff_eq(&u, 1e-9) && pv.abs_diff_eq(&v, 1e-9));
+        }
+        assert!(projection.is_valid_pixel(&PixelIndex(320.0 + 520.0, 240.0)));
+        assert!(!projection.is_valid_pixel(&PixelIndex(320.0 + 540.0, 240.0)));
+        assert!(!projection.is_valid_ray(&CameraRay::new(0.2, 0.0, -1.0)));
+    }
+    #[test]
+    fn test_extended_unified_matches_unified() {
+        // with beta = 1 the extended model is the unified model with alpha = xi / (1 + xi)
+        let xi = 0.9;
+        let unified = UnifiedCamera::new(400.0, 410.0, 320.0, 240.0, xi);
+        let extended = ExtendedUnifiedCamera::new(
+            400.0 / (1.0 + xi),
+            410.0 / (1.0 + xi),
+            320.0,
+            240.0,
+            xi / (1.0 + xi),
+            1.0,
+        );
+        for (x, y, z) in [(0.3, -0.2, 1.0), (1.0, 0.5, -0.2), (-2.0, 0.1, 0.3)] {
+            let ray = CameraRay::new(x, y, z);
+            let a = unified.project(&ray);
+            let b = extended.project(&ray);
+            assert!(a.0.abs_diff_eq(&b.0, 1e-9) && a.1.abs_diff_eq(&b.1, 1e-9));
+        }
+    }
+    #[test]
+    fn test_double_sphere() {
+        let projection = DoubleSphere::new(313.0, 314.0, 320.0, 240.0, -0.18, 0.59);
+        assert_pixel2pixel(&projection);
+        assert_ray2ray(&projection, 120f64.to_radians());
+        for ((x, y, z), (u, v)) in [
+            ((0.3, -0.2, 1.0), (429.9617385510269, 166.4579639935624)),
+            ((1.0, 0.5, -0.2), (904.3341544194714, 533.1005183509809)),
+        ] {
+            let PixelIndex(pu, pv) = projection.project(&CameraRay::new(x, y, z));
+            assert!(pu.abs_diff_eq(&u, 1e-9) && pv.abs_diff_eq(&v, 1e-9));
+        }
+        assert!(projection.is_valid_ray(&CameraRay::new(1.0, 0.5, -0.2)));
+        assert!(!projection.is_valid_ray(&CameraRay::new(0.1, 0.0, -1.0)));
+        assert!(projection.is_valid_pixel(&PixelIndex(320.0 + 720.0, 240.0)));
+        assert!(!projection.is_valid_pixel(&PixelIndex(320.0 + 745.0, 240.0)));
+        // fov beyond 180°
+        let ray = CameraRay::new(1.0, 0.0, -0.3);
+        assert_eq!(projection.unproject(&projection.project(&ray)), ray);
+    }
+    fn scaramuzza() -> Scaramuzza {
+        let mut projection = Scaramuzza {
+            polynomial: vec![-189.8232, 0.0, 2.776063e-03, -2.511565e-06, 1.008096e-08],
+            inverse_polynomial: Vec::new(),
+            cx: 489.391537,
+            cy: 374.175359,
+            c: 0.998627,
+            d: -0.002161,
+            e: 0.000889,
+        };
+        projection.fit_inverse_polynomial(14, 700.0);
+        projection
+    }
+    #[test]
+    fn test_scaramuzza() {
+        let projection = scaramuzza();
+        // the inverse polynomial is a fit, so the roundtrip is only approximate
+        for (u, v) in [
+            (489.391537, 374.175359),
+            (0.0, 0.0),
+            (700.0, 100.0),
+            (1023.0, 767.0),
+        ] {
+            let src = PixelIndex(u, v);
+            let dst = projection.project(&projection.unproject(&src));
+            assert!(src.0.abs_diff_eq(&dst.0, 5e-2) && src.1.abs_diff_eq(&dst.1, 5e-2));
+        }
+        // the optical axis is imaged at the center, a ray to the right lands right of it
+        let center = projection.project(&CameraRay::new(0.0, 0.0, 1.0));
+        assert!(center.0.abs_diff_eq(&489.391537, 1e-9) && center.1.abs_diff_eq(&374.175359, 1e-9));
+        let right = projection.project(&CameraRay::new(0.3, 0.0, 1.0));
+        assert!(right.0 > 489.391537 + 10.0 && right.1.abs_diff_eq(&374.175359, 1.0));
+        let ray = projection.unproject(&PixelIndex(489.391537, 374.175359));
+        assert_eq!(ray, CameraRay::new(0.0, 0.0, 1.0));
+    }
+    fn bspline() -> BSplineCamera {
+        let fisheye = Stereographic::new(300.0, 310.0, 320.0, 240.0, 0.0);
+        BSplineCamera::fit(&fisheye, (0.0, 0.0), (640.0, 480.0), 14, 11).unwrap()
+    }
+    #[test]
+    fn test_bspline() {
+        let projection = bspline();
+        assert_pixel2pixel(&projection);
+        assert_ray2ray(&projection, 0.9);
+        // the fit follows the parametric model closely
+        let fisheye = Stereographic::new(300.0, 310.0, 320.0, 240.0, 0.0);
+        for (u, v) in [(320.0, 240.0), (10.0, 20.0), (600.0, 100.0), (639.0, 479.0)] {
+            let pixel = PixelIndex(u, v);
+            let expected = fisheye.unproject(&pixel).vector;
+            let angle = projection.unproject(&pixel).vector.angle(&expected);
+            assert!(angle < 1e-4, "angle {} at {:?}", angle, pixel);
+        }
+        let solver = IterativeSolver {
+            max_iterations: 1,
+            ..IterativeSolver::default()
+        };
+        assert!(matches!(
+            projection.project_with(&CameraRay::new(0.4, 0.3, 1.0), &solver),
+            Err(DistortionError::NotConverged { iterations: 1, .. })
+        ));
+        assert!(projection.is_valid_pixel(&PixelIndex(0.0, 480.0)));
+        assert!(!projection.is_valid_pixel(&PixelIndex(-1.0, 240.0)));
+        // the projection is clamped to the calibrated area
+        assert!(projection
+            .try_project(&CameraRay::new(0.1, -0.2, 1.0))
+            .is_some());
+        assert!(projection
+            .try_project(&CameraRay::new(1.0, 0.0, 0.0))
+            .is_none());
+        // the border itself is inside, just beyond it the solver ends on the clamp
+        let border = projection.unproject(&PixelIndex(0.0, 200.0));
+        let pixel = projection.try_project(&border).unwrap();
+        assert!(pixel.0.abs_diff_eq(&0.0, 1e-9) && pixel.1.abs_diff_eq(&200.0, 1e-6));
+        let outside = CameraRay {
+            vector: Rotation3::from_euler_angles(0.0, -1e-4, 0.0) * border.vector,
+        };
+        assert_eq!(
+            projection.project_with(&outside, &IterativeSolver::default()),
+            Err(DistortionError::OutOfDomain)
+        );
+        assert_eq!(projection.project(&outside).0, 0.0);
+    }
+    #[test]
+    fn test_bspline_fit_errors() {
+        let fisheye = Stereographic::new(300.0, 310.0, 320.0, 240.0, 0.0);
+        let fit = |min, max, columns, rows| BSplineCamera::fit(&fisheye, min, max, columns, rows);
+        assert_eq!(
+            fit((0.0, 0.0), (640.0, 480.0), 3, 5),
+            Err(BSplineError::TooFewControlPoints {
+                columns: 3,
+                rows: 5
+            })
+        );
+        assert_eq!(
+            fit((0.0, 0.0), (0.0, 480.0), 6, 5),
+            Err(BSplineError::EmptyArea)
+        );
+        // the orthographic projection sees only the pixels inside of its image circle
+        let orthographic = Orthographic::new(300.0, 300.0, 320.0, 240.0, 0.0);
+        assert!(matches!(
+            BSplineCamera::fit(&orthographic, (0.0, 0.0), (640.0, 480.0), 6, 5),
+            Err(BSplineError::InvalidSample { .. })
+        ));
+    }
+    #[test]
+    fn test_bspline_serialization() {
+        let projection = bspline();
+        let serialized = toml::to_string(&projection).unwrap();
+        let deserialized: BSplineCamera = toml::from_str(&serialized).unwrap();
+        assert_eq!(deserialized, projection);
+
+        let mut malformed = projection.clone();
+        malformed.grid.pop();
+        let result = toml::from_str::<BSplineCamera>(&toml::to_string(&malformed).unwrap());
+        assert!(result
+            .unwrap_err()
+            .to_string()
+            .contains("expected 154 control points, found 153"));
+        malformed.grid.truncate(9);
+        (malformed.columns, malformed.rows) = (3, 3);
+        let result = toml::from_str::<BSplineCamera>(&toml::to_string(&malformed).unwrap());
+        assert!(result.is_err());
+        assert_eq!(
+            BSplineCamera::new((0.0, 0.0), (0.0, 480.0), 4, 4, vec![[0.0; 3]; 16]),
+            Err(BSplineError::EmptyArea)
+        );
+    }
+    fn assert_affine_intrinsics<P: AffineIntrinsics + CameraProjection>(projection: &P) {
+        for (x, y, z) in [(0.0, 0.0, 1.0), (0.3, -0.2, 1.0), (-0.4, 0.5, 0.8)] {
+            let ray = CameraRay::new(x, y, z);
+            let PixelIndex(u, v) = projection.project(&ray);
+
+            let scaled = projection.scale(0.5, 0.25).project(&ray);
+            assert!(scaled.0.abs_diff_eq(&(0.5 * (u + 0.5) - 0.5), 1e-9));
+            assert!(scaled.1.abs_diff_eq(&(0.25 * (v + 0.5) - 0.5), 1e-9));
+
+            let cropped = projection.crop((100, 50, 200, 100)).project(&ray);
+            assert!(cropped.0.abs_diff_eq(&(u - 100.0), 1e-9));
+            assert!(cropped.1.abs_diff_eq(&(v - 50.0), 1e-9));
+
+            // pixels 0 to 3 become pixel 0, which is centered at 1.5
+            let binned = projection.bin(4).project(&ray);
+            assert!(binned.0.abs_diff_eq(&((u - 1.5) / 4.0), 1e-9));
+            assert!(binned.1.abs_diff_eq(&((v - 1.5) / 4.0), 1e-9));
+
+            let level = projection.pyramid_level(2).project(&ray);
+            assert!(level.0.abs_diff_eq(&(u / 4.0), 1e-9));
+            assert!(level.1.abs_diff_eq(&(v / 4.0), 1e-9));
+        }
+    }
+    #[test]
+    fn test_affine_intrinsics() {
+        assert_affine_intrinsics(&Pinhole::new(400.0, 410.0, 320.0, 240.0, 0.5));
+        assert_affine_intrinsics(&Fisheye {
+            fx: 300.0,
+            fy: 310.0,
+            cx: 320.0,
+            cy: 240.0,
+            skew: 0.3,
+        });
+        let pinhole = Pinhole::new(400.0, 410.0, 319.5, 239.5, 0.0);
+        // the center of a 640 x 480 image stays the center of the scaled image
+        let scaled = pinhole.scale(0.5, 0.5);
+        assert_eq!((scaled.fx, scaled.cx, scaled.cy), (200.0, 159.5, 119.5));
+        assert_eq!(pinhole.bin(2), scaled);
+    }
+    #[test]
+    #[should_panic(expected = "invalid scale")]
+    fn test_scale_by_zero() {
+        Pinhole::new(400.0, 400.0, 320.0, 240.0, 0.0).scale(0.0, 1.0);
+    }
+    #[test]
+    fn test_image_transform() {
+        // the corners of a 4 x 3 image, the top left one after `cv::rotate` and `cv::flip`
+        let resolution = (4, 3);
+        let corner = |transform: ImageTransform, u, v| {
+            let PixelIndex(u, v) = transform.pixel(&PixelIndex(u, v), resolution);
+            (u, v)
+        };
+        assert_eq!(
+            corner(ImageTransform::Rotate90Clockwise, 0.0, 2.0),
+            (0.0, 0.0)
+        );
+        assert_eq!(corner(ImageTransform::Rotate180, 3.0, 2.0), (0.0, 0.0));
+        assert_eq!(
+            corner(ImageTransform::Rotate90Counterclockwise, 3.0, 0.0),
+            (0.0, 0.0)
+        );
+        assert_eq!(corner(ImageTransform::FlipHorizontal, 3.0, 0.0), (0.0, 0.0));
+        assert_eq!(corner(ImageTransform::FlipVertical, 0.0, 2.0), (0.0, 0.0));
+        assert_eq!(
+            ImageTransform::Rotate90Clockwise.resolution(resolution),
+            (3, 4)
+        );
+        for transform in [
+            ImageTransform::Rotate90Clockwise,
+            ImageTransform::Rotate180,
+            ImageTransform::Rotate90Counterclockwise,
+            ImageTransform::FlipHorizontal,
+            ImageTransform::FlipVertical,
+        ] {
+            let inverse = transform.inverse();
+            let pixel = transform.pixel(&PixelIndex(1.0, 2.0), resolution);
+            let pixel = inverse.pixel(&pixel, transform.resolution(resolution));
+            assert_eq!((pixel.0, pixel.1), (1.0, 2.0), "{:?}", transform);
+        }
+    }
+    #[test]
+    fn test_try_project() {
+        let pinhole = Pinhole::new(400.0, 410.0, 320.0, 240.0, 0.5);
+        // the pinhole projects the direction of the ray
+        let PixelIndex(u, v) = pinhole
+            .try_project(&CameraRay::new(0.4, -0.2, 2.0))
+            .unwrap();
+        let expected = pinhole.project(&CameraRay::new(0.2, -0.1, 1.0));
+        assert_eq!((u, v), (expected.0, expected.1));
+        assert!(pinhole
+            .try_project(&CameraRay::new(0.4, -0.2, -2.0))
+            .is_none());
+        assert!(pinhole
+            .try_project(&CameraRay::new(0.4, -0.2, 0.0))
+            .is_none());
+
+        // the fisheye covers everything but the rear axis
+        assert!(PROJECTION
+            .try_project(&CameraRay::new(0.1, 0.0, -1.0))
+            .is_some());
+        assert!(PROJECTION
+            .try_project(&CameraRay::new(0.0, 0.0, -1.0))
+            .is_none());
+
+        let orthographic = Orthographic::new(300.0, 310.0, 320.0, 240.0, 0.0);
+        assert!(orthographic
+            .try_project(&CameraRay::new(1.0, 0.0, 0.0))
+            .is_some());
+        assert!(orthographic
+            .try_project(&CameraRay::new(1.0, 0.0, -0.1))
+            .is_none());
+        assert!(!orthographic.is_valid_pixel(&PixelIndex(320.0 + 301.0, 240.0)));
+
+        let any = AnyProjection::UnifiedCamera(UnifiedCamera::new(400.0, 400.0, 320.0, 240.0, 2.0));
+        assert!(any.try_project(&CameraRay::new(1.0, 0.0, -0.4)).is_some());
+        assert!(any.try_project(&CameraRay::new(1.0, 0.0, -0.6)).is_none());
+        assert!(!any.is_valid_pixel(&PixelIndex(320.0 + 232.0, 240.0)));
+    }
+    #[test]
+    fn test_any_projection() {
+        let config = r#"
+            type = "DoubleSphere"
+            [parameters]
+            fx = 300.0
+            fy = 310.0
+            cx = 320.0
+            cy = 240.0
+            xi = -0.2
+            alpha = 0.6
+        "#;
+        let projection: AnyProjection = toml::from_str(config).unwrap();
+        let expected = DoubleSphere::new(300.0, 310.0, 320.0, 240.0, -0.2, 0.6);
+        assert_eq!(projection, AnyProjection::DoubleSphere(expected));
+        let ray = CameraRay::new(0.3, -0.2, 1.0);
+        let (dst, src) = (projection.project(&ray), expected.project(&ray));
+        assert_eq!((dst.0, dst.1), (src.0, src.1));
+        assert_pixel2pixel(&projection);
+
+        let unknown = "type = \"Cylindrical\"\nparameters = {}";
+        assert!(toml::from_str::<AnyProjection>(unknown).is_err());
+    }
+    #[test]
+    fn test_stereographic() {
+        let projection = Stereographic::new(300.0, 310.0, 320.0, 240.0, 0.2);
+        assert_pixel2pixel(&projection);
+        assert_ray2ray(&projection, 170f64.to_radians());
+        // a ray at 90° lands at twice the focal length
+        let PixelIndex(u, _) = projection.project(&CameraRay::new(1.0, 0.0, 0.0));
+        assert!(u.abs_diff_eq(&(320.0 + 600.0), 1e-9));
+    }
+    #[test]
+    fn test_equisolid() {
+        let projection = Equisolid::new(300.0, 310.0, 320.0, 240.0, 0.2);
+        assert_pixel2pixel(&projection);
+        assert_ray2ray(&projection, 170f64.to_radians());
+        let PixelIndex(_, v) = projection.project(&CameraRay::new(0.0, 1.0, 0.0));
+        assert!(v.abs_diff_eq(&(240.0 + 310.0 * 2f64.sqrt()), 1e-9));
+    }
+    #[test]
+    fn test_orthographic() {
+        // all test pixels have to be inside the image of the hemisphere
+        let projection = Orthographic::new(600.0, 610.0, 320.0, 240.0, 0.0);
+        assert_pixel2pixel(&projection);
+        assert_ray2ray(&projection, 89f64.to_radians());
+        let PixelIndex(u, _) = projection.project(&CameraRay::new(-1.0, 0.0, 0.0));
+        assert!(u.abs_diff_eq(&(320.0 - 600.0), 1e-9));
+    }
 }
@@ -0,0 +1,598 @@
+use crate::camera::{CameraModel, CameraRay, PixelIndex};
+use crate::distortion::CameraDistortion;
+use crate::projection::CameraProjection;
+
+use image::{ImageBuffer, Pixel, Primitive};
+use nalgebra::RealField;
+use num_traits::NumCast;
+use rayon::prelude::*;
+use std::f32::consts::PI;
+
+/// how the color at a fractional source location is computed, the kernels follow `cv::remap`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Interpolation {
+    Nearest,
+    #[default]
+    Bilinear,
+    /// cubic convolution on a 4x4 neighbourhood with a = -0.75
+    Bicubic,
+    /// windowed sinc on a 8x8 neighbourhood
+    Lanczos4,
+}
+
+/// how source locations outside the image are handled, the modes follow the OpenCV border types
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Border {
+    /// outside pixels have the given color, one value per channel
+    Constant([f32; 4]),
+    /// `aaaaaa|abcdefgh|hhhhhhh`
+    Replicate,
+    /// `fedcba|abcdefgh|hgfedcb`
+    Reflect,
+    /// `gfedcb|abcdefgh|gfedcba`
+    Reflect101,
+    /// `cdefgh|abcdefgh|abcdefg`
+    Wrap,
+    /// destination pixels which would need an outside pixel are not modified
+    Transparent,
+}
+impl Default for Border {
+    fn default() -> Self {
+        Border::Constant([0.0; 4])
+    }
+}
+
+impl Border {
+    /// maps an index along an axis of length `len` into the image, `None` if there is no source pixel
+    #[inline]
+    fn index(self, p: i64, len: i64) -> Option<i64> {
+        if 0 <= p && p < len {
+            return Some(p);
+        }
+        match self {
+            Border::Constant(_) | Border::Transparent => None,
+            Border::Replicate => Some(p.clamp(0, len - 1)),
+            Border::Reflect => {
+                let m = p.rem_euclid(2 * len);
+                Some(if m < len { m } else { 2 * len - 1 - m })
+            }
+            Border::Reflect101 if len == 1 => Some(0),
+            Border::Reflect101 => {
+                let period = 2 * (len - 1);
+                let m = p.rem_euclid(period);
+                Some(if m < len { m } else { period - m })
+            }
+            Border::Wrap => Some(p.rem_euclid(len)),
+        }
+    }
+}
+
+impl Interpolation {
+    /// returns the first tap, the number of taps and their weights along one axis
+    #[inline]
+    fn taps(self, x: f32) -> (i64, usize, [f32; 8]) {
+        let mut weights = [0.0; 8];
+        match self {
+            Interpolation::Nearest => {
+                weights[0] = 1.0;
+                (x.round() as i64, 1, weights)
+            }
+            Interpolation::Bilinear => {
+                let x0 = x.floor();
+                let t = x - x0;
+                weights[0] = 1.0 - t;
+                weights[1] = t;
+                (x0 as i64, 2, weights)
+            }
+            Interpolation::Bicubic => {
+                const A: f32 = -0.75;
+                let x0 = x.floor();
+                let t = x - x0;
+                weights[0] =
+                    ((A * (t + 1.0) - 5.0 * A) * (t + 1.0) + 8.0 * A) * (t + 1.0) - 4.0 * A;
+                weights[1] = ((A + 2.0) * t - (A + 3.0)) * t * t + 1.0;
+                weights[2] = ((A + 2.0) * (1.0 - t) - (A + 3.0)) * (1.0 - t) * (1.0 - t) + 1.0;
+                weights[3] = 1.0 - weights[0] - weights[1] - weights[2];
+                (x0 as i64 - 1, 4, weights)
+            }
+            Interpolation::Lanczos4 => {
+                let x0 = x.floor();
+                let t = x - x0;
+                let mut sum = 0.0;
+                for (i, weight) in weights.iter_mut().enumerate() {
+                    let d = (t + 3.0 - i as f32) * PI;
+                    *weight = if d.abs() < f32::EPSILON {
+                        1.0
+                    } else {
+                        4.0 * d.sin() * (d / 4.0).sin() / (d * d)
+                    };
+                    sum += *weight;
+                }
+                weights.iter_mut().for_each(|weight| *weight /= sum);
+                (x0 as i64 - 3, 8, weights)
+            }
+        }
+    }
+}
+
+/// a precomputed lookup which resamples images of a camera into the image of a target projection
+///
+/// for every target pixel we answer the question "where would the ideal ray of this pixel have landed in the
+/// source camera?" once, applying the map afterwards only interpolates the source image.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RemapMap {
+    source: (u32, u32),
+    resolution: (u32, u32),
+    /// row major, the fractional source location for each target pixel
+    coordinates: Vec<(f32, f32)>,
+    interpolation: Interpolation,
+    border: Border,
+}
+
+impl RemapMap {
+    /// `source` is the resolution of the images taken with `camera`, it has to match the resolution of the
+    /// camera if that is known and panics otherwise. `resolution` is the one of the produced images
+    ///
+    /// the models are evaluated in their scalar type `S`, e.g. `f32` models give a faster but less accurate map
+    pub fn new<S, T, V, P>(
+        camera: &CameraModel<T, V>,
+        source: (u32, u32),
+        target: &P,
+        resolution: (u32, u32),
+    ) -> Self
+    where
+        S: RealField + Copy,
+        T: CameraProjection<S> + Sync,
+        V: CameraDistortion<S> + Sync,
+        P: CameraProjection<S> + Sync,
+    {
+        assert!(
+            camera.resolution().is_none_or(|camera| camera == source),
+            "the source resolution differs from the resolution of the camera"
+        );
+        Self::build(camera, source, resolution, |pixel| {
+            target
+                .is_valid_pixel(pixel)
+                .then(|| target.unproject(pixel))
+        })
+    }
+    /// like `new` with the resolutions stored in the models, the target may be distorted as well.
+    /// `None` if one of the models has no resolution
+    pub fn from_models<S, T, V, P, W>(
+        camera: &CameraModel<T, V>,
+        target: &CameraModel<P, W>,
+    ) -> Option<Self>
+    where
+        S: RealField + Copy,
+        T: CameraProjection<S> + Sync,
+        V: CameraDistortion<S> + Sync,
+        P: CameraProjection<S> + Sync,
+        W: CameraDistortion<S> + Sync,
+    {
+        let source = camera.resolution()?;
+        let resolution = target.resolution()?;
+        Some(Self::build(camera, source, resolution, |pixel| {
+            if !target.projection().is_valid_pixel(pixel) {
+                return None;
+            }
+            target.unproject(pixel).ok()
+        }))
+    }
+    /// `ray` is the ray of a target pixel, `None` leaves the pixel unmapped
+    fn build<S, T, V, F>(
+        camera: &CameraModel<T, V>,
+        source: (u32, u32),
+        resolution: (u32, u32),
+        ray: F,
+    ) -> Self
+    where
+        S: RealField + Copy,
+        T: CameraProjection<S> + Sync,
+        V: CameraDistortion<S> + Sync,
+        F: Fn(&PixelIndex<S>) -> Option<CameraRay<S>> + Sync,
+    {
+        let (width, height) = resolution;
+        let to_f32 = |x: S| x.to_subset_unchecked() as f32;
+        let ray = &ray;
+        let coordinates = (0..height)
+            .into_par_iter()
+            .flat_map_iter(|v| {
+                (0..width).map(move |u| {
+                    let pixel =
+                        PixelIndex(nalgebra::convert(u as f64), nalgebra::convert(v as f64));
+                    // rays outside of the domain of the camera stay unmapped
+                    match ray(&pixel).and_then(|ray| camera.try_project(ray)) {
+                        Some(PixelIndex(x, y)) => (to_f32(x), to_f32(y)),
+                        None => (f32::NAN, f32::NAN),
+                    }
+                })
+            })
+            .collect();
+        Self {
+            source,
+            resolution,
+            coordinates,
+            interpolation: Interpolation::default(),
+            border: Border::default(),
+        }
+    }
+    pub fn with_interpolation(mut self, interpolation: Interpolation) -> Self {
+        self.interpolation = interpolation;
+        self
+    }
+    pub fn interpolation(&self) -> Interpolation {
+        self.interpolation
+    }
+    pub fn with_border(mut self, border: Border) -> Self {
+        self.border = border;
+        self
+    }
+    pub fn border(&self) -> Border {
+        self.border
+    }
+    pub fn source_resolution(&self) -> (u32, u32) {
+        self.source
+    }
+    pub fn resolution(&self) -> (u32, u32) {
+        self.resolution
+    }
+
+    /// resamples `img` into a newly allocated image, which starts out black for the transparent border
+    ///
+    /// # Panics
+    /// if `img` does not have the source resolution of the map
+    pub fn apply<P>(
+        &self,
+        img: &ImageBuffer<P, Vec<P::Subpixel>>,
+    ) -> ImageBuffer<P, Vec<P::Subpixel>>
+    where
+        P: Pixel + Send + Sync,
+        P::Subpixel: Send + Sync,
+    {
+        let (width, height) = self.resolution;
+        let mut res = ImageBuffer::new(width, height);
+        self.apply_into(img, &mut res);
+        res
+    }
+
+    /// like `apply` but writes into an existing image
+    ///
+    /// # Panics
+    /// if the images do not have the source and target resolution of the map
+    pub fn apply_into<P>(
+        &self,
+        img: &ImageBuffer<P, Vec<P::Subpixel>>,
+        res: &mut ImageBuffer<P, Vec<P::Subpixel>>,
+    ) where
+        P: Pixel + Send + Sync,
+        P::Subpixel: Send + Sync,
+    {
+        assert_eq!(img.dimensions(), self.source, "source image resolution");
+        assert_eq!(res.dimensions(), self.resolution, "target image resolution");
+        let channels = P::CHANNEL_COUNT as usize;
+        let row_len = self.resolution.0 as usize;
+        let src = img.as_raw();
+        res.par_chunks_mut(row_len * channels)
+            .zip(self.coordinates.par_chunks(row_len))
+            .for_each(|(row, row_map)| {
+                for (px, &(x, y)) in row.chunks_exact_mut(channels).zip(row_map) {
+                    self.remap_pixel(src, px, x, y);
+                }
+            });
+    }
+
+    #[inline]
+    fn remap_pixel<S: Primitive>(&self, src: &[S], px: &mut [S], x: f32, y: f32) {
+        let channels = px.len();
+        let (width, height) = (self.source.0 as i64, self.source.1 as i64);
+        let border = self.border;
+        // also rejects nan, and keeps the tap arithmetic far away from overflowing
+        if !(x.abs() < COORDINATE_LIMIT && y.abs() < COORDINATE_LIMIT) {
+            if let Border::Constant(color) = border {
+                fill(px, color);
+            }
+            return;
+        }
+        let (x0, nx, wx) = self.interpolation.taps(x);
+        let (y0, ny, wy) = self.interpolation.taps(y);
+        if nx == 1 {
+            // nearest neighbour is a plain copy
+            match (border.index(x0, width), border.index(y0, height)) {
+                (Some(xx), Some(yy)) => {
+                    let idx = (yy * width + xx) as usize * channels;
+                    px.copy_from_slice(&src[idx..idx + channels]);
+                }
+                _ => {
+                    if let Border::Constant(color) = border {
+                        fill(px, color);
+                    }
+                }
+            }
+            return;
+        }
+        let mut acc = [0.0f32; 4];
+        for (j, wy) in wy.iter().take(ny).enumerate() {
+            if *wy == 0.0 {
+                continue;
+            }
+            let yy = border.index(y0 + j as i64, height);
+            for (i, wx) in wx.iter().take(nx).enumerate() {
+                if *wx == 0.0 {
+                    continue;
+                }
+                let weight = wx * wy;
+                match (border.index(x0 + i as i64, width), yy, border) {
+                    (Some(xx), Some(yy), _) => {
+                        let idx = (yy * width + xx) as usize * channels;
+                        for (acc, value) in acc.iter_mut().zip(&src[idx..idx + channels]) {
+                            *acc += weight * value.to_f32().unwrap_or_default();
+                        }
+                    }
+                    (_, _, Border::Constant(color)) => {
+                        for (acc, value) in acc.iter_mut().zip(color) {
+                            *acc += weight * value;
+                        }
+                    }
+                    // transparent, a tap is missing so the destination is kept
+                    _ => return,
+                }
+            }
+        }
+        fill(px, acc);
+    }
+}
+
+/// coordinates beyond this are treated as not mapped
+const COORDINATE_LIMIT: f32 = 1e9;
+
+#[inline]
+fn fill<S: Primitive>(px: &mut [S], values: [f32; 4]) {
+    for (dst, value) in px.iter_mut().zip(values) {
+        *dst = saturate(value);
+    }
+}
+
+/// converts back to the subpixel type, integer types are rounded and clamped to their range
+#[inline]
+fn saturate<S: Primitive>(value: f32) -> S {
+    match S::from(value) {
+        Some(exact) if exact.to_f32() == Some(value) => exact,
+        _ => {
+            let min = S::DEFAULT_MIN_VALUE.to_f32().unwrap_or(f32::MIN);
+            let max = S::DEFAULT_MAX_VALUE.to_f32().unwrap_or(f32::MAX);
+            <S as NumCast>::from(value.round().clamp(min, max)).unwrap_or(S::DEFAULT_MIN_VALUE)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Equisolid, Ideal, Pinhole, PlumbBob};
+    use approx::assert_abs_diff_eq;
+    use image::{Luma, Rgb, RgbImage};
+
+    fn test_image() -> RgbImage {
+        RgbImage::from_fn(8, 6, |u, v| Rgb([u as u8, v as u8, 7]))
+    }
+
+    #[test]
+    fn test_identity() {
+        let projection = Pinhole::new(4.0, 4.0, 4.0, 3.0, 0.0);
+        let camera = CameraModel::new(projection, Ideal {});
+        let img = test_image();
+        for interpolation in [
+            Interpolation::Nearest,
+            Interpolation::Bilinear,
+            Interpolation::Bicubic,
+            Interpolation::Lanczos4,
+        ] {
+            let map = RemapMap::new(&camera, (8, 6), &projection, (8, 6))
+                .with_interpolation(interpolation);
+            assert_eq!(map.apply(&img), img, "{:?}", interpolation);
+        }
+    }
+    #[test]
+    fn test_from_models() {
+        let projection = Pinhole::new(4.0, 4.0, 4.0, 3.0, 0.0);
+        let camera = CameraModel::new(projection, Ideal {});
+        let target = CameraModel::new(Pinhole::new(4.0, 4.0, 5.0, 3.0, 0.0), Ideal {});
+        assert!(RemapMap::from_models(&camera, &target.clone().with_resolution((10, 6))).is_none());
+
+        let camera = camera.with_resolution((8, 6));
+        let target = target.with_resolution((10, 6));
+        let map = RemapMap::from_models(&camera, &target).unwrap();
+        assert_eq!(map.source_resolution(), (8, 6));
+        assert_eq!(
+            map,
+            RemapMap::new(&camera, (8, 6), target.projection(), (10, 6))
+        );
+    }
+    #[test]
+    #[should_panic(expected = "resolution of the camera")]
+    fn test_source_resolution_mismatch() {
+        let projection = Pinhole::new(4.0, 4.0, 4.0, 3.0, 0.0);
+        let camera = CameraModel::new(projection, Ideal {}).with_resolution((8, 6));
+        RemapMap::new(&camera, (10, 6), &projection, (8, 6));
+    }
+    #[test]
+    fn test_shift_and_resolution() {
+        let projection = Pinhole::new(4.0, 4.0, 4.0, 3.0, 0.0);
+        let camera = CameraModel::new(projection, Ideal {});
+        // moving the principal point by one pixel shifts the image content
+        let target = Pinhole::new(4.0, 4.0, 5.0, 3.0, 0.0);
+        let map = RemapMap::new(&camera, (8, 6), &target, (10, 6));
+        let res = map.apply(&test_image());
+        assert_eq!(res.dimensions(), (10, 6));
+        assert_eq!(res[(0, 2)], Rgb([0, 0, 0]));
+        assert_eq!(res[(1, 2)], Rgb([0, 2, 7]));
+        assert_eq!(res[(8, 5)], Rgb([7, 5, 7]));
+        assert_eq!(res[(9, 5)], Rgb([0, 0, 0]));
+    }
+    #[test]
+    fn test_apply_into_keeps_unmapped() {
+        let projection = Pinhole::new(4.0, 4.0, 4.0, 3.0, 0.0);
+        let camera = CameraModel::new(projection, Ideal {});
+        let target = Pinhole::new(4.0, 4.0, 4.0, 5.0, 0.0);
+        let map = RemapMap::new(&camera, (8, 6), &target, (8, 6)).with_border(Border::Transparent);
+        let img = ImageBuffer::from_pixel(8, 6, Luma([10u8]));
+        let mut res = ImageBuffer::from_pixel(8, 6, Luma([200u8]));
+        map.apply_into(&img, &mut res);
+        assert_eq!(res[(3, 1)], Luma([200]));
+        assert_eq!(res[(3, 2)], Luma([10]));
+
+        // with interpolation a single missing tap keeps the destination
+        let target = Pinhole::new(4.0, 4.0, 4.0, 4.5, 0.0);
+        let map = RemapMap::new(&camera, (8, 6), &target, (8, 6)).with_border(Border::Transparent);
+        let mut res = ImageBuffer::from_pixel(8, 6, Luma([200u8]));
+        map.apply_into(&img, &mut res);
+        assert_eq!(res[(3, 1)], Luma([200]));
+        assert_eq!(res[(3, 2)], Luma([10]));
+    }
+    #[test]
+    fn test_border_index() {
+        let indices = |border: Border| -> Vec<Option<i64>> {
+            (-4..0).chain(5..9).map(|p| border.index(p, 5)).collect()
+        };
+        assert_eq!(indices(Border::Transparent), vec![None; 8]);
+        assert_eq!(
+            indices(Border::Replicate),
+            [0, 0, 0, 0, 4, 4, 4, 4].map(Some)
+        );
+        assert_eq!(indices(Border::Reflect), [3, 2, 1, 0, 4, 3, 2, 1].map(Some));
+        assert_eq!(
+            indices(Border::Reflect101),
+            [4, 3, 2, 1, 3, 2, 1, 0].map(Some)
+        );
+        assert_eq!(indices(Border::Wrap), [1, 2, 3, 4, 0, 1, 2, 3].map(Some));
+        assert_eq!(Border::Reflect101.index(-3, 1), Some(0));
+    }
+    #[test]
+    fn test_border_modes() {
+        let projection = Pinhole::new(4.0, 4.0, 4.0, 3.0, 0.0);
+        let camera = CameraModel::new(projection, Ideal {});
+        // the target sees two columns left of the source image
+        let target = Pinhole::new(4.0, 4.0, 6.0, 3.0, 0.0);
+        let img = test_image();
+        for interpolation in [Interpolation::Nearest, Interpolation::Bilinear] {
+            let map =
+                RemapMap::new(&camera, (8, 6), &target, (8, 6)).with_interpolation(interpolation);
+            let column = |border: Border| {
+                let res = map.clone().with_border(border).apply(&img);
+                [res[(0, 1)], res[(1, 1)], res[(2, 1)]].map(|px| px.0[0])
+            };
+            assert_eq!(column(Border::default()), [0, 0, 0]);
+            assert_eq!(column(Border::Constant([9.0, 0.0, 0.0, 0.0])), [9, 9, 0]);
+            assert_eq!(column(Border::Replicate), [0, 0, 0]);
+            assert_eq!(column(Border::Reflect), [1, 0, 0]);
+            assert_eq!(column(Border::Reflect101), [2, 1, 0]);
+            assert_eq!(column(Border::Wrap), [6, 7, 0]);
+            assert_eq!(column(Border::Transparent), [0, 0, 0]);
+        }
+    }
+    #[test]
+    fn test_unmapped_coordinates() {
+        let mut map = RemapMap::new(
+            &CameraModel::new(Pinhole::new(1.0, 1.0, 0.0, 0.0, 0.0), Ideal {}),
+            (8, 6),
+            &Pinhole::new(1.0, 1.0, 0.0, 0.0, 0.0),
+            (1, 1),
+        );
+        map.coordinates[0] = (f32::NAN, 0.0);
+        let res = map
+            .with_border(Border::Constant([3.0, 0.0, 0.0, 0.0]))
+            .apply(&test_image());
+        assert_eq!(res[(0, 0)], Rgb([3, 0, 0]));
+    }
+    #[test]
+    fn test_single_precision() {
+        let distortion = PlumbBob::new(0.1, -0.05, 0.001, 0.002, 0.0);
+        let camera = CameraModel::new(Pinhole::new(6.0, 6.0, 4.0, 3.0, 0.0), distortion);
+        let target = Pinhole::new(5.0, 5.0, 4.0, 3.0, 0.0);
+        let map = RemapMap::new(&camera, (8, 6), &target, (8, 6));
+
+        let distortion = PlumbBob::new(0.1f32, -0.05, 0.001, 0.002, 0.0);
+        let camera = CameraModel::new(Pinhole::new(6.0f32, 6.0, 4.0, 3.0, 0.0), distortion);
+        let target = Pinhole::new(5.0f32, 5.0, 4.0, 3.0, 0.0);
+        let single = RemapMap::new(&camera, (8, 6), &target, (8, 6));
+        for (a, b) in map.coordinates.iter().zip(&single.coordinates) {
+            assert_abs_diff_eq!(a.0, b.0, epsilon = 1e-4);
+            assert_abs_diff_eq!(a.1, b.1, epsilon = 1e-4);
+        }
+    }
+    #[test]
+    fn test_outside_of_domain() {
+        // the equisolid target sees the full sphere, the pinhole camera only up to 90°
+        let camera = CameraModel::new(Pinhole::new(1.0, 1.0, 4.0, 3.0, 0.0), Ideal {});
+        let target = Equisolid::new(2.0, 2.0, 4.0, 3.0, 0.0);
+        let map = RemapMap::new(&camera, (8, 6), &target, (8, 6));
+        let index = |u: usize, v: usize| v * 8 + u;
+        assert_abs_diff_eq!(map.coordinates[index(4, 3)].0, 4.0);
+        // 97° away from the optical axis
+        assert!(map.coordinates[index(7, 3)].0.is_nan());
+        // outside of the image of the sphere
+        assert!(map.coordinates[index(0, 0)].0.is_nan());
+    }
+    #[test]
+    fn test_distorted_camera_to_equisolid() {
+        // the equisolid target unprojects to unit rays, the distortion sees them on the image plane
+        let distortion = PlumbBob::new(0.1, -0.05, 0.001, 0.002, 0.0);
+        let camera = CameraModel::new(Pinhole::new(6.0, 6.0, 4.0, 3.0, 0.0), distortion);
+        let target = Equisolid::new(5.0, 5.0, 4.0, 3.0, 0.0);
+        let map = RemapMap::new(&camera, (8, 6), &target, (8, 6));
+        for (index, &(x, y)) in map.coordinates.iter().enumerate() {
+            let pixel = PixelIndex((index % 8) as f64, (index / 8) as f64);
+            let vector = target.unproject(&pixel).vector;
+            let ray = CameraRay::new(vector.x / vector.z, vector.y / vector.z, 1.0);
+            let PixelIndex(u, v) = camera.project(ray);
+            assert_abs_diff_eq!(x, u as f32, epsilon = 1e-4);
+            assert_abs_diff_eq!(y, v as f32, epsilon = 1e-4);
+        }
+    }
+    #[test]
+    fn test_half_pixel_shift() {
+        let projection = Pinhole::new(4.0, 4.0, 4.0, 3.0, 0.0);
+        let camera = CameraModel::new(projection, Ideal {});
+        let target = Pinhole::new(4.0, 4.0, 3.5, 3.0, 0.0);
+        let img = ImageBuffer::from_fn(8, 6, |u, _| Luma([(u * 20) as u8]));
+
+        let map = RemapMap::new(&camera, (8, 6), &target, (8, 6));
+        let res = map.apply(&img);
+        assert_eq!(res[(2, 0)], Luma([50]));
+
+        // nearest rounds half way cases away from zero
+        let res = map
+            .clone()
+            .with_interpolation(Interpolation::Nearest)
+            .apply(&img);
+        assert_eq!(res[(2, 0)], Luma([60]));
+
+        // the higher order kernels reproduce a linear ramp away from the border
+        for interpolation in [Interpolation::Bicubic, Interpolation::Lanczos4] {
+            let res = map.clone().with_interpolation(interpolation).apply(&img);
+            assert_eq!(res[(3, 3)], Luma([70]), "{:?}", interpolation);
+        }
+    }
+    #[test]
+    fn test_kernels_partition_of_unity() {
+        for interpolation in [
+            Interpolation::Bilinear,
+            Interpolation::Bicubic,
+            Interpolation::Lanczos4,
+        ] {
+            for x in [0.0, 0.1, 0.25, 0.5, 0.9] {
+                let (_, n, weights) = interpolation.taps(x);
+                let sum: f32 = weights.iter().take(n).sum();
+                assert_abs_diff_eq!(sum, 1.0, epsilon = 1e-6);
+            }
+        }
+    }
+    #[test]
+    fn test_saturate() {
+        assert_eq!(saturate::<u8>(300.0), 255);
+        assert_eq!(saturate::<u8>(-3.0), 0);
+        assert_eq!(saturate::<u8>(127.6), 128);
+        assert_eq!(saturate::<u16>(1000.4), 1000);
+        assert_eq!(saturate::<f32>(1.25), 1.25);
+    }
+}